@@ -0,0 +1,152 @@
+use anyhow::Result;
+use clap::{Parser, ValueEnum};
+use std::path::PathBuf;
+
+use grpc_speedrace::config::Config;
+use grpc_speedrace::export::ExportFormat;
+use grpc_speedrace::referee::ScoringMode;
+
+// Command-line options - anything set here overrides the config file
+#[derive(Parser)]
+#[command(version, about = "Race Solana gRPC streams head-to-head")]
+pub struct Cli {
+    /// Path to the TOML configuration file
+    #[arg(short, long, default_value = "config.toml")]
+    pub config: PathBuf,
+
+    /// Number of slots to track
+    #[arg(long)]
+    pub max_slots: Option<usize>,
+
+    /// Stop after max slots (true) or keep a rolling window (false)
+    #[arg(long, value_name = "BOOL")]
+    pub stop_at_max: Option<bool>,
+
+    /// Commitment level: processed, confirmed or finalized
+    #[arg(long)]
+    pub commitment: Option<String>,
+
+    /// Slots to skip before counting winners
+    #[arg(long)]
+    pub warmup_slots: Option<usize>,
+
+    /// Close each race this many milliseconds after its first report
+    #[arg(long, value_name = "MS")]
+    pub race_deadline_ms: Option<u64>,
+
+    /// Write periodic and final summaries as JSON lines to this file ("-" for stdout)
+    #[arg(long, value_name = "PATH")]
+    pub json_output: Option<String>,
+
+    /// Export every race's raw finish times to this file
+    #[arg(long, value_name = "PATH")]
+    pub export: Option<String>,
+
+    /// Format of the race export
+    #[arg(long, value_enum)]
+    pub export_format: Option<ExportFormatArg>,
+
+    /// Serve Prometheus metrics on this address (e.g. 0.0.0.0:9090)
+    #[arg(long, value_name = "ADDR")]
+    pub metrics_listen: Option<String>,
+
+    /// How missed and late races are scored in percentiles and rankings
+    #[arg(long, value_enum)]
+    pub scoring: Option<ScoringModeArg>,
+
+    /// Reports further than this behind the winner count as late (milliseconds)
+    #[arg(long, value_name = "MS")]
+    pub scoring_timeout_ms: Option<u64>,
+
+    /// Only race the named streams (repeat the flag or separate names with commas)
+    #[arg(short, long = "stream", value_name = "NAME", value_delimiter = ',')]
+    pub streams: Vec<String>,
+}
+
+// Command-line spelling of ExportFormat, so the library doesn't depend on clap
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormatArg {
+    Csv,
+    Ndjson,
+}
+
+impl From<ExportFormatArg> for ExportFormat {
+    fn from(format: ExportFormatArg) -> Self {
+        match format {
+            ExportFormatArg::Csv => ExportFormat::Csv,
+            ExportFormatArg::Ndjson => ExportFormat::Ndjson,
+        }
+    }
+}
+
+// Command-line spelling of ScoringMode
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ScoringModeArg {
+    Reported,
+    Timeout,
+    Infinite,
+}
+
+impl From<ScoringModeArg> for ScoringMode {
+    fn from(mode: ScoringModeArg) -> Self {
+        match mode {
+            ScoringModeArg::Reported => ScoringMode::Reported,
+            ScoringModeArg::Timeout => ScoringMode::Timeout,
+            ScoringModeArg::Infinite => ScoringMode::Infinite,
+        }
+    }
+}
+
+impl Cli {
+    pub fn apply_overrides(&self, config: &mut Config) -> Result<()> {
+        if let Some(max_slots) = self.max_slots {
+            config.max_slots = max_slots;
+        }
+
+        if let Some(stop_at_max) = self.stop_at_max {
+            config.stop_at_max = stop_at_max;
+        }
+
+        if let Some(commitment) = &self.commitment {
+            config.commitment = commitment.clone();
+        }
+
+        if let Some(warmup_slots) = self.warmup_slots {
+            config.warmup_slots = warmup_slots;
+        }
+
+        if let Some(race_deadline_ms) = self.race_deadline_ms {
+            config.race_deadline_ms = race_deadline_ms;
+        }
+
+        if let Some(json_output) = &self.json_output {
+            config.output.json_path = Some(json_output.clone());
+        }
+
+        if let Some(export) = &self.export {
+            config.output.export_path = Some(export.clone());
+        }
+
+        if let Some(export_format) = self.export_format {
+            config.output.export_format = export_format.into();
+        }
+
+        if let Some(metrics_listen) = &self.metrics_listen {
+            config.metrics.listen = Some(metrics_listen.clone());
+        }
+
+        if let Some(scoring) = self.scoring {
+            config.scoring.mode = scoring.into();
+        }
+
+        if let Some(scoring_timeout_ms) = self.scoring_timeout_ms {
+            config.scoring.timeout_ms = scoring_timeout_ms;
+        }
+
+        if !self.streams.is_empty() {
+            config.select_streams(&self.streams)?;
+        }
+
+        Ok(())
+    }
+}
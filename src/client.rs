@@ -1,4 +1,5 @@
 use anyhow::Result;
+use std::time::Duration;
 use tonic::transport::ClientTlsConfig;
 use yellowstone_grpc_client::GeyserGrpcClient;
 use crate::config::StreamConfig;
@@ -19,6 +20,42 @@ impl GrpcClient {
             builder = builder.x_token(Some(token))?;
         }
         
+        // Channel tuning - anything not configured keeps the builder default
+        let tuning = self.config.grpc;
+        if let Some(ms) = tuning.connect_timeout_ms {
+            builder = builder.connect_timeout(Duration::from_millis(ms));
+        }
+        if let Some(ms) = tuning.timeout_ms {
+            builder = builder.timeout(Duration::from_millis(ms));
+        }
+        if let Some(ms) = tuning.http2_keep_alive_interval_ms {
+            builder = builder.http2_keep_alive_interval(Duration::from_millis(ms));
+        }
+        if let Some(ms) = tuning.keep_alive_timeout_ms {
+            builder = builder.keep_alive_timeout(Duration::from_millis(ms));
+        }
+        if let Some(enabled) = tuning.keep_alive_while_idle {
+            builder = builder.keep_alive_while_idle(enabled);
+        }
+        if let Some(enabled) = tuning.http2_adaptive_window {
+            builder = builder.http2_adaptive_window(enabled);
+        }
+        if let Some(size) = tuning.initial_stream_window_size {
+            builder = builder.initial_stream_window_size(size);
+        }
+        if let Some(size) = tuning.initial_connection_window_size {
+            builder = builder.initial_connection_window_size(size);
+        }
+        if let Some(enabled) = tuning.tcp_nodelay {
+            builder = builder.tcp_nodelay(enabled);
+        }
+        if let Some(ms) = tuning.tcp_keepalive_ms {
+            builder = builder.tcp_keepalive(Some(Duration::from_millis(ms)));
+        }
+        if let Some(limit) = tuning.max_decoding_message_size {
+            builder = builder.max_decoding_message_size(limit);
+        }
+        
         let client = builder
             .tls_config(ClientTlsConfig::new().with_native_roots())?
             .connect()
@@ -26,4 +63,4 @@ impl GrpcClient {
             
         Ok(client)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,56 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use yellowstone_grpc_proto::prost_types::Timestamp;
+
+// High-resolution race clock shared by all streams, anchored to wall-clock time
+// so provider-side timestamps can be placed on the same timeline
+#[derive(Debug)]
+pub struct RaceClock {
+    start: Instant,
+    start_unix_ns: u128,
+}
+
+impl Default for RaceClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RaceClock {
+    pub fn new() -> Self {
+        let start_unix_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or(0);
+
+        Self {
+            start: Instant::now(),
+            start_unix_ns,
+        }
+    }
+
+    // Nanoseconds since the race clock started
+    pub fn timestamp(&self, instant: Instant) -> u128 {
+        instant.duration_since(self.start).as_nanos()
+    }
+
+    // The instant a race clock timestamp refers to
+    pub fn instant(&self, timestamp: u128) -> Instant {
+        self.start + Duration::from_nanos(timestamp as u64)
+    }
+
+    // Place a provider `created_at` timestamp on the race clock timeline
+    // Times before the clock started have no place on it - clamping them would fake a delay
+    pub fn timestamp_from_proto(&self, created_at: &Timestamp) -> Option<u128> {
+        if created_at.seconds < 0 || created_at.nanos < 0 {
+            return None;
+        }
+
+        let unix_ns = created_at.seconds as u128 * 1_000_000_000 + created_at.nanos as u128;
+        unix_ns.checked_sub(self.start_unix_ns)
+    }
+
+    // Place a provider timestamp in Unix milliseconds on the race clock timeline, if it is after the start
+    pub fn timestamp_from_unix_ms(&self, unix_ms: u64) -> Option<u128> {
+        (unix_ms as u128 * 1_000_000).checked_sub(self.start_unix_ns)
+    }
+}
@@ -1,7 +1,19 @@
 use serde::{Deserialize, Serialize};
 use anyhow::Result;
+use backoff::ExponentialBackoff;
+use std::fmt;
 use std::fs;
-use yellowstone_grpc_proto::prelude::CommitmentLevel;
+use std::net::SocketAddr;
+use std::path::Path;
+use std::time::Duration;
+use tracing::warn;
+use yellowstone_grpc_proto::prelude::{
+    CommitmentLevel, SubscribeRequest, SubscribeRequestFilterAccounts, SubscribeRequestFilterBlocks,
+    SubscribeRequestFilterBlocksMeta, SubscribeRequestFilterSlots, SubscribeRequestFilterTransactions,
+};
+
+use crate::export::ExportFormat;
+use crate::referee::{RaceKind, ScoringMode};
 
 #[derive(Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -13,15 +25,236 @@ pub struct Config {
     pub commitment: String,
     #[serde(default = "default_warmup_slots")]
     pub warmup_slots: usize,
+    #[serde(default = "default_races")]
+    pub races: Vec<RaceKind>,
+    #[serde(default = "default_race_deadline_ms")]
+    pub race_deadline_ms: u64,
+    // Ask providers to replay from the last slot seen when a stream reconnects
+    #[serde(default)]
+    pub resume_from_last_slot: bool,
+    // Interval between client pings used to measure round-trip time (0 disables them)
+    #[serde(default = "default_ping_interval_ms")]
+    pub ping_interval_ms: u64,
+    #[serde(default)]
+    pub transactions: TransactionFilterConfig,
+    #[serde(default)]
+    pub accounts: AccountFilterConfig,
+    #[serde(default)]
+    pub blocks: BlockFilterConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub metrics: MetricsConfig,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+    #[serde(default)]
+    pub watchdog: WatchdogConfig,
     pub streams: Vec<StreamConfig>,
 }
 
+// Protocol a stream is raced over
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamKind {
+    // Yellowstone gRPC Geyser endpoint
+    #[default]
+    Grpc,
+    // Solana JSON-RPC PubSub WebSocket (slotSubscribe / slotsUpdatesSubscribe)
+    Websocket,
+    // Raw shreds forwarded to a local UDP port, e.g. by a Jito ShredStream proxy
+    Shreds,
+    // Slot races recorded in an NDJSON race export, replayed from the file
+    Replay,
+    // A source registered in code with RaceBuilder::source - the endpoint is only a label
+    Custom,
+}
+
+impl StreamKind {
+    // Whether streams of this kind can deliver updates for the race kind
+    pub fn supports(&self, race: RaceKind) -> bool {
+        match self {
+            // A custom source decides for itself which updates it reports
+            StreamKind::Grpc | StreamKind::Custom => true,
+            StreamKind::Websocket => matches!(race, RaceKind::Slot | RaceKind::SlotStatus),
+            StreamKind::Shreds | StreamKind::Replay => race == RaceKind::Slot,
+        }
+    }
+}
+
+impl fmt::Display for StreamKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StreamKind::Grpc => write!(f, "gRPC"),
+            StreamKind::Websocket => write!(f, "WebSocket"),
+            StreamKind::Shreds => write!(f, "shreds"),
+            StreamKind::Replay => write!(f, "replay"),
+            StreamKind::Custom => write!(f, "custom"),
+        }
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct StreamConfig {
     pub name: String,
+    #[serde(default)]
+    pub kind: StreamKind,
     pub endpoint: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub access_token: Option<String>,
+    // Overrides of the [reconnect] policy for this stream
+    #[serde(default)]
+    pub reconnect: ReconnectOverrides,
+    // HTTP/2 and gRPC channel tuning, for gRPC streams
+    #[serde(default)]
+    pub grpc: GrpcTuning,
+}
+
+// Account filters applied to the transaction race subscription
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct TransactionFilterConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vote: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed: Option<bool>,
+    #[serde(default)]
+    pub account_include: Vec<String>,
+    #[serde(default)]
+    pub account_exclude: Vec<String>,
+    #[serde(default)]
+    pub account_required: Vec<String>,
+}
+
+// Accounts and owners watched by the account race subscription
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct AccountFilterConfig {
+    #[serde(default)]
+    pub account: Vec<String>,
+    #[serde(default)]
+    pub owner: Vec<String>,
+}
+
+// Contents requested for the full block race subscription
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BlockFilterConfig {
+    #[serde(default)]
+    pub account_include: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_transactions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_accounts: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_entries: Option<bool>,
+}
+
+// Where machine-readable results are written
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OutputConfig {
+    // JSON summary destination: a file path, or "-" for stdout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_path: Option<String>,
+    // Raw per-race finish times for offline analysis
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_path: Option<String>,
+    #[serde(default = "default_export_format")]
+    pub export_format: ExportFormat,
+}
+
+// Optional Prometheus endpoint
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    // Address to serve /metrics on, e.g. "0.0.0.0:9090"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listen: Option<String>,
+}
+
+// How missed and late races count towards latency percentiles and rankings
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ScoringConfig {
+    #[serde(default = "default_scoring_mode")]
+    pub mode: ScoringMode,
+    // Reports this far behind the winner are scored as late
+    #[serde(default = "default_scoring_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+// Backoff between reconnection attempts
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ReconnectConfig {
+    #[serde(default = "default_initial_interval_ms")]
+    pub initial_interval_ms: u64,
+    #[serde(default = "default_max_interval_ms")]
+    pub max_interval_ms: u64,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    // Randomize each interval by up to this fraction (0.0 - 1.0)
+    #[serde(default = "default_jitter")]
+    pub jitter: f64,
+    // Keep reconnecting indefinitely - max_elapsed_ms is ignored
+    #[serde(default = "default_retry_forever")]
+    pub retry_forever: bool,
+    // Give up once reconnection attempts have been failing for this long
+    #[serde(default = "default_max_elapsed_ms")]
+    pub max_elapsed_ms: u64,
+}
+
+// Per-stream reconnect settings - anything left out falls back to [reconnect]
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct ReconnectOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_interval_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_interval_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jitter: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry_forever: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_elapsed_ms: Option<u64>,
+}
+
+// Per-stream HTTP/2 and gRPC channel settings - anything left out keeps the client default
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GrpcTuning {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_timeout_ms: Option<u64>,
+    // Time allowed to open the subscription - the stream itself never times out
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http2_keep_alive_interval_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive_timeout_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive_while_idle: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http2_adaptive_window: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_stream_window_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_connection_window_size: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_nodelay: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tcp_keepalive_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_decoding_message_size: Option<usize>,
+}
+
+// Forces a reconnect when a stream stays open but falls behind the leader
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WatchdogConfig {
+    #[serde(default = "default_watchdog_enabled")]
+    pub enabled: bool,
+    // Stall once the stream's latest slot is this far behind the leader's
+    #[serde(default = "default_max_slots_behind")]
+    pub max_slots_behind: u64,
+    // Stall once the stream has sent nothing for this long while the leader is ahead
+    #[serde(default = "default_max_silence_ms")]
+    pub max_silence_ms: u64,
 }
 
 fn default_max_slots() -> usize {
@@ -40,22 +273,267 @@ fn default_warmup_slots() -> usize {
     10
 }
 
+fn default_races() -> Vec<RaceKind> {
+    vec![RaceKind::Slot]
+}
+
+fn default_race_deadline_ms() -> u64 {
+    5000
+}
+
+fn default_ping_interval_ms() -> u64 {
+    10_000
+}
+
+fn default_export_format() -> ExportFormat {
+    ExportFormat::Csv
+}
+
+fn default_scoring_mode() -> ScoringMode {
+    ScoringMode::Timeout
+}
+
+fn default_scoring_timeout_ms() -> u64 {
+    1000
+}
+
+// Every setting at its default and no streams - add some before starting a race
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_slots: default_max_slots(),
+            stop_at_max: default_stop_at_max(),
+            commitment: default_commitment(),
+            warmup_slots: default_warmup_slots(),
+            races: default_races(),
+            race_deadline_ms: default_race_deadline_ms(),
+            resume_from_last_slot: false,
+            ping_interval_ms: default_ping_interval_ms(),
+            transactions: TransactionFilterConfig::default(),
+            accounts: AccountFilterConfig::default(),
+            blocks: BlockFilterConfig::default(),
+            output: OutputConfig::default(),
+            metrics: MetricsConfig::default(),
+            scoring: ScoringConfig::default(),
+            reconnect: ReconnectConfig::default(),
+            watchdog: WatchdogConfig::default(),
+            streams: Vec::new(),
+        }
+    }
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            json_path: None,
+            export_path: None,
+            export_format: default_export_format(),
+        }
+    }
+}
+
+fn default_initial_interval_ms() -> u64 {
+    500
+}
+
+fn default_max_interval_ms() -> u64 {
+    60_000
+}
+
+fn default_multiplier() -> f64 {
+    1.5
+}
+
+fn default_jitter() -> f64 {
+    0.5
+}
+
+fn default_retry_forever() -> bool {
+    true
+}
+
+fn default_max_elapsed_ms() -> u64 {
+    900_000
+}
+
+fn default_watchdog_enabled() -> bool {
+    true
+}
+
+fn default_max_slots_behind() -> u64 {
+    50
+}
+
+fn default_max_silence_ms() -> u64 {
+    10_000
+}
+
+impl Default for WatchdogConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_watchdog_enabled(),
+            max_slots_behind: default_max_slots_behind(),
+            max_silence_ms: default_max_silence_ms(),
+        }
+    }
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            initial_interval_ms: default_initial_interval_ms(),
+            max_interval_ms: default_max_interval_ms(),
+            multiplier: default_multiplier(),
+            jitter: default_jitter(),
+            retry_forever: default_retry_forever(),
+            max_elapsed_ms: default_max_elapsed_ms(),
+        }
+    }
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_scoring_mode(),
+            timeout_ms: default_scoring_timeout_ms(),
+        }
+    }
+}
+
+impl StreamConfig {
+    // A stream of the given kind with no access token or reconnect overrides
+    pub fn new(name: impl Into<String>, kind: StreamKind, endpoint: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            kind,
+            endpoint: endpoint.into(),
+            access_token: None,
+            reconnect: ReconnectOverrides::default(),
+            grpc: GrpcTuning::default(),
+        }
+    }
+
+    // Local UDP address a shred stream listens on - the endpoint, with an optional udp:// prefix
+    pub fn shred_listen_addr(&self) -> Result<SocketAddr> {
+        let addr = self.endpoint.strip_prefix("udp://").unwrap_or(&self.endpoint);
+        addr.parse()
+            .map_err(|e| anyhow::anyhow!("Invalid shred listen address '{}' for stream '{}': {}", self.endpoint, self.name, e))
+    }
+}
+
 impl Config {
-    pub fn from_file() -> Result<Self> {
-        let content = fs::read_to_string("config.toml")
-            .map_err(|e| anyhow::anyhow!("Failed to read config.toml: {}", e))?;
+    // Parse the config file - call validate() once any overrides have been applied
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?;
 
-        let config: Config = toml::from_str(&content)
-            .map_err(|e| anyhow::anyhow!("Failed to parse config.toml: {}", e))?;
+        toml::from_str(&content)
+            .map_err(|e| anyhow::anyhow!("Failed to parse {}: {}", path.display(), e))
+    }
+
+    // Parse a config in the config.toml format
+    pub fn from_toml(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| anyhow::anyhow!("Failed to parse config: {}", e))
+    }
 
-        if config.streams.is_empty() {
-            return Err(anyhow::anyhow!("No streams configured in config.toml"));
+    pub fn validate(&self) -> Result<()> {
+        if self.streams.is_empty() {
+            return Err(anyhow::anyhow!("No streams configured"));
         }
 
         // Validate commitment level
-        config.commitment_level()?;
+        self.commitment_level()?;
+
+        if self.races.is_empty() {
+            return Err(anyhow::anyhow!("No races configured"));
+        }
+
+        if self.race_deadline_ms == 0 {
+            return Err(anyhow::anyhow!("race_deadline_ms must be greater than 0"));
+        }
+
+        for stream in &self.streams {
+            self.reconnect_policy(stream).validate()
+                .map_err(|e| anyhow::anyhow!("Invalid reconnect policy for stream '{}': {}", stream.name, e))?;
+            stream.grpc.validate()
+                .map_err(|e| anyhow::anyhow!("Invalid gRPC tuning for stream '{}': {}", stream.name, e))?;
+            if stream.kind != StreamKind::Grpc && stream.grpc != GrpcTuning::default() {
+                warn!("gRPC tuning for {} stream '{}' is ignored", stream.kind, stream.name);
+            }
+            if stream.grpc.http2_adaptive_window == Some(true)
+                && (stream.grpc.initial_stream_window_size.is_some() || stream.grpc.initial_connection_window_size.is_some())
+            {
+                warn!("http2_adaptive_window overrides the initial window sizes of stream '{}'", stream.name);
+            }
+        }
+
+        for stream in &self.streams {
+            if let Some(race) = self.races.iter().find(|race| !stream.kind.supports(**race)) {
+                return Err(anyhow::anyhow!("{} stream '{}' can't race {}", stream.kind, stream.name, race));
+            }
+            match stream.kind {
+                StreamKind::Shreds => {
+                    stream.shred_listen_addr()?;
+                }
+                StreamKind::Replay if !Path::new(&stream.endpoint).is_file() => {
+                    return Err(anyhow::anyhow!("Replay file '{}' for stream '{}' not found", stream.endpoint, stream.name));
+                }
+                _ => {}
+            }
+        }
+
+        if self.streams.iter().any(|s| matches!(s.kind, StreamKind::Websocket | StreamKind::Shreds))
+            && self.races.contains(&RaceKind::Slot)
+            && self.commitment_level()? != CommitmentLevel::Processed
+        {
+            warn!("WebSocket and shred streams see slots before they are confirmed - set commitment = \"processed\" to race them against the first gRPC slot update");
+        }
+
+        if let Some(listen) = &self.metrics.listen {
+            listen.parse::<SocketAddr>()
+                .map_err(|e| anyhow::anyhow!("Invalid metrics listen address '{}': {}", listen, e))?;
+        }
+
+        if self.races.contains(&RaceKind::Transaction) && self.transactions.is_unfiltered() {
+            warn!("Transaction race has no account filters - every transaction on the network will be raced");
+        }
+
+        if self.races.contains(&RaceKind::Account) && self.accounts.is_unfiltered() {
+            return Err(anyhow::anyhow!(
+                "Account race requires at least one entry in accounts.account or accounts.owner"
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Keep only the named streams, in the order they appear in the config
+    pub fn select_streams(&mut self, names: &[String]) -> Result<()> {
+        for name in names {
+            if !self.streams.iter().any(|s| s.name == *name) {
+                return Err(anyhow::anyhow!(
+                    "Unknown stream '{}'. Configured streams: {}",
+                    name,
+                    self.streams.iter().map(|s| s.name.as_str()).collect::<Vec<_>>().join(", ")
+                ));
+            }
+        }
 
-        Ok(config)
+        self.streams.retain(|s| names.contains(&s.name));
+        Ok(())
+    }
+
+    // The [reconnect] policy with the stream's own overrides applied
+    pub fn reconnect_policy(&self, stream: &StreamConfig) -> ReconnectConfig {
+        let overrides = &stream.reconnect;
+        ReconnectConfig {
+            initial_interval_ms: overrides.initial_interval_ms.unwrap_or(self.reconnect.initial_interval_ms),
+            max_interval_ms: overrides.max_interval_ms.unwrap_or(self.reconnect.max_interval_ms),
+            multiplier: overrides.multiplier.unwrap_or(self.reconnect.multiplier),
+            jitter: overrides.jitter.unwrap_or(self.reconnect.jitter),
+            retry_forever: overrides.retry_forever.unwrap_or(self.reconnect.retry_forever),
+            max_elapsed_ms: overrides.max_elapsed_ms.unwrap_or(self.reconnect.max_elapsed_ms),
+        }
     }
 
     pub fn commitment_level(&self) -> Result<CommitmentLevel> {
@@ -69,4 +547,156 @@ impl Config {
             )),
         }
     }
-}
\ No newline at end of file
+
+    // Build the subscription request shared by every stream
+    // Filters are named after their race kind so updates can be routed back to the right race
+    pub fn subscribe_request(&self) -> Result<SubscribeRequest> {
+        let mut request = SubscribeRequest {
+            commitment: Some(self.commitment_level()? as i32),
+            ..Default::default()
+        };
+
+        for kind in &self.races {
+            match kind {
+                RaceKind::Slot => {
+                    request.slots.insert(kind.to_string(), SubscribeRequestFilterSlots {
+                        filter_by_commitment: Some(true),
+                        interslot_updates: Some(false),
+                    });
+                }
+                RaceKind::SlotStatus => {
+                    // Every status of every slot, regardless of the commitment level
+                    request.slots.insert(kind.to_string(), SubscribeRequestFilterSlots {
+                        filter_by_commitment: Some(false),
+                        interslot_updates: Some(true),
+                    });
+                }
+                RaceKind::Transaction => {
+                    request.transactions.insert(kind.to_string(), self.transactions.to_filter());
+                }
+                RaceKind::Account => {
+                    request.accounts.insert(kind.to_string(), self.accounts.to_filter());
+                }
+                RaceKind::Block => {
+                    request.blocks.insert(kind.to_string(), self.blocks.to_filter());
+                }
+                RaceKind::BlockMeta => {
+                    request.blocks_meta.insert(kind.to_string(), SubscribeRequestFilterBlocksMeta {});
+                }
+            }
+        }
+
+        Ok(request)
+    }
+}
+
+impl TransactionFilterConfig {
+    fn is_unfiltered(&self) -> bool {
+        self.account_include.is_empty() && self.account_exclude.is_empty() && self.account_required.is_empty()
+    }
+
+    pub fn to_filter(&self) -> SubscribeRequestFilterTransactions {
+        SubscribeRequestFilterTransactions {
+            vote: self.vote,
+            failed: self.failed,
+            signature: None,
+            account_include: self.account_include.clone(),
+            account_exclude: self.account_exclude.clone(),
+            account_required: self.account_required.clone(),
+        }
+    }
+}
+
+impl AccountFilterConfig {
+    fn is_unfiltered(&self) -> bool {
+        self.account.is_empty() && self.owner.is_empty()
+    }
+
+    pub fn to_filter(&self) -> SubscribeRequestFilterAccounts {
+        SubscribeRequestFilterAccounts {
+            account: self.account.clone(),
+            owner: self.owner.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl BlockFilterConfig {
+    pub fn to_filter(&self) -> SubscribeRequestFilterBlocks {
+        SubscribeRequestFilterBlocks {
+            account_include: self.account_include.clone(),
+            include_transactions: self.include_transactions,
+            include_accounts: self.include_accounts,
+            include_entries: self.include_entries,
+        }
+    }
+}
+
+// Largest HTTP/2 flow control window (2^31 - 1)
+const MAX_WINDOW_SIZE: u32 = i32::MAX as u32;
+
+impl GrpcTuning {
+    fn validate(&self) -> Result<()> {
+        let durations = [
+            ("connect_timeout_ms", self.connect_timeout_ms),
+            ("timeout_ms", self.timeout_ms),
+            ("http2_keep_alive_interval_ms", self.http2_keep_alive_interval_ms),
+            ("keep_alive_timeout_ms", self.keep_alive_timeout_ms),
+            ("tcp_keepalive_ms", self.tcp_keepalive_ms),
+        ];
+        if let Some((name, _)) = durations.iter().find(|(_, ms)| *ms == Some(0)) {
+            return Err(anyhow::anyhow!("{} must be greater than 0", name));
+        }
+
+        let windows = [
+            ("initial_stream_window_size", self.initial_stream_window_size),
+            ("initial_connection_window_size", self.initial_connection_window_size),
+        ];
+        for (name, size) in windows {
+            if size.is_some_and(|size| size == 0 || size > MAX_WINDOW_SIZE) {
+                return Err(anyhow::anyhow!("{} must be between 1 and {}", name, MAX_WINDOW_SIZE));
+            }
+        }
+
+        if self.max_decoding_message_size == Some(0) {
+            return Err(anyhow::anyhow!("max_decoding_message_size must be greater than 0"));
+        }
+        if self.http2_keep_alive_interval_ms.is_none()
+            && (self.keep_alive_timeout_ms.is_some() || self.keep_alive_while_idle.is_some())
+        {
+            return Err(anyhow::anyhow!("keep_alive_timeout_ms and keep_alive_while_idle need http2_keep_alive_interval_ms"));
+        }
+        Ok(())
+    }
+}
+
+impl ReconnectConfig {
+    fn validate(&self) -> Result<()> {
+        if self.initial_interval_ms == 0 {
+            return Err(anyhow::anyhow!("initial_interval_ms must be greater than 0"));
+        }
+        if self.max_interval_ms < self.initial_interval_ms {
+            return Err(anyhow::anyhow!("max_interval_ms must be at least initial_interval_ms"));
+        }
+        // NaN fails every comparison, so non-finite values are ruled out first
+        if !self.multiplier.is_finite() || self.multiplier < 1.0 {
+            return Err(anyhow::anyhow!("multiplier must be a finite number of at least 1.0"));
+        }
+        if !self.jitter.is_finite() || !(0.0..=1.0).contains(&self.jitter) {
+            return Err(anyhow::anyhow!("jitter must be between 0.0 and 1.0"));
+        }
+        Ok(())
+    }
+
+    pub fn to_backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff {
+            initial_interval: Duration::from_millis(self.initial_interval_ms),
+            current_interval: Duration::from_millis(self.initial_interval_ms),
+            max_interval: Duration::from_millis(self.max_interval_ms),
+            multiplier: self.multiplier,
+            randomization_factor: self.jitter,
+            max_elapsed_time: (!self.retry_forever).then(|| Duration::from_millis(self.max_elapsed_ms)),
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,156 @@
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use tokio::sync::mpsc;
+use tracing::error;
+
+use crate::referee::RaceClosed;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+// Writes one record per race result with every stream's raw finish time
+pub struct RaceExporter {
+    format: ExportFormat,
+    stream_names: Vec<String>,
+    writer: BufWriter<File>,
+}
+
+impl RaceExporter {
+    pub fn new(path: &str, format: ExportFormat, stream_names: Vec<String>) -> Result<Self> {
+        let file = File::create(path)
+            .map_err(|e| anyhow::anyhow!("Failed to create export file {}: {}", path, e))?;
+
+        let mut exporter = Self {
+            format,
+            stream_names,
+            writer: BufWriter::new(file),
+        };
+
+        if format == ExportFormat::Csv {
+            exporter.write_csv_header()?;
+        }
+
+        Ok(exporter)
+    }
+
+    // Export races as they close, flushing whenever the queue drains, until the referee shuts down
+    pub async fn run(mut self, mut closed_rx: mpsc::UnboundedReceiver<RaceClosed>) {
+        while let Some(closed) = closed_rx.recv().await {
+            if let Err(e) = self.export(&closed) {
+                error!("Failed to export {}: {}", closed.result.key, e);
+            }
+            if closed_rx.is_empty() {
+                if let Err(e) = self.flush() {
+                    error!("Failed to flush export file: {}", e);
+                }
+            }
+        }
+
+        if let Err(e) = self.flush() {
+            error!("Failed to flush export file: {}", e);
+        }
+    }
+
+    pub fn export(&mut self, closed: &RaceClosed) -> Result<()> {
+        match self.format {
+            ExportFormat::Csv => self.write_csv_record(closed),
+            ExportFormat::Ndjson => self.write_ndjson_record(closed),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn write_csv_header(&mut self) -> Result<()> {
+        let mut columns = vec![
+            "race".to_string(),
+            "slot".to_string(),
+            "key".to_string(),
+            "winner".to_string(),
+            "winner_timestamp_ns".to_string(),
+            "close_reason".to_string(),
+        ];
+        for name in &self.stream_names {
+            columns.push(format!("{}_timestamp_ns", name));
+            columns.push(format!("{}_delta_ns", name));
+        }
+
+        let header: Vec<String> = columns.iter().map(|c| csv_field(c)).collect();
+        writeln!(self.writer, "{}", header.join(","))?;
+        Ok(())
+    }
+
+    fn write_csv_record(&mut self, closed: &RaceClosed) -> Result<()> {
+        let result = &closed.result;
+        let mut fields = vec![
+            result.key.kind().to_string(),
+            result.key.slot().to_string(),
+            csv_field(&result.key.to_string()),
+            csv_field(&result.winner),
+            result.winner_timestamp.to_string(),
+            closed.reason.to_string(),
+        ];
+
+        // Streams that never reported, or reported after the race closed, get empty cells
+        for name in &self.stream_names {
+            match result.finish_times.get(name) {
+                Some(timestamp) => {
+                    fields.push(timestamp.to_string());
+                    fields.push(timestamp.saturating_sub(result.winner_timestamp).to_string());
+                }
+                None => {
+                    fields.push(String::new());
+                    fields.push(String::new());
+                }
+            }
+        }
+
+        writeln!(self.writer, "{}", fields.join(","))?;
+        Ok(())
+    }
+
+    fn write_ndjson_record(&mut self, closed: &RaceClosed) -> Result<()> {
+        let result = &closed.result;
+        let streams: serde_json::Map<String, serde_json::Value> = result.finish_times.iter()
+            .map(|(name, timestamp)| {
+                let record = json!({
+                    "timestamp_ns": timestamp,
+                    "delta_ns": timestamp.saturating_sub(result.winner_timestamp),
+                    "created_at_ns": result.created_at.get(name),
+                });
+                (name.clone(), record)
+            })
+            .collect();
+
+        let record = json!({
+            "race": result.key.kind(),
+            "slot": result.key.slot(),
+            "key": result.key.to_string(),
+            "winner": result.winner,
+            "winner_timestamp_ns": result.winner_timestamp,
+            "close_reason": closed.reason,
+            "streams": streams,
+        });
+
+        writeln!(self.writer, "{}", record)?;
+        Ok(())
+    }
+}
+
+// Quote a CSV field when it contains a delimiter, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
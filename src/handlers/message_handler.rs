@@ -1,54 +1,109 @@
 use anyhow::Result;
 use futures::SinkExt;
-use tracing::{error, info, warn};
+use std::collections::VecDeque;
+use tracing::{debug, error, info, warn};
 use yellowstone_grpc_proto::prelude::*;
 
 use super::update_handlers::UpdateHandlers;
 use crate::referee::SharedReferee;
 
+// Unanswered pings remembered for matching pongs - older ones are given up on
+const MAX_PENDING_PINGS: usize = 64;
+
 pub struct MessageHandler {
     update_handlers: UpdateHandlers,
+    referee: SharedReferee,
     stream_id: String,
+    next_ping_id: i32,
+    // (ping id, send timestamp) of pings still waiting for their pong
+    pending_pings: VecDeque<(i32, u128)>,
+    // Highest slot of any update received, to resume from after a reconnect
+    last_slot: Option<u64>,
 }
 
 impl MessageHandler {
     pub fn new(stream_id: String, referee: SharedReferee) -> Self {
         Self {
-            update_handlers: UpdateHandlers::new(stream_id.clone(), referee),
+            update_handlers: UpdateHandlers::new(stream_id.clone(), referee.clone()),
+            referee,
             stream_id,
+            next_ping_id: 1,
+            pending_pings: VecDeque::new(),
+            last_slot: None,
         }
     }
 
+    pub fn last_slot(&self) -> Option<u64> {
+        self.last_slot
+    }
+
+    fn record_slot(&mut self, slot: u64) {
+        self.last_slot = Some(self.last_slot.map_or(slot, |last| last.max(slot)));
+    }
+
+    // Send a ping with a fresh id and remember when it left, to time its pong
+    pub async fn send_ping(
+        &mut self,
+        timestamp: u128,
+        subscribe_tx: &mut (impl SinkExt<SubscribeRequest, Error = futures::channel::mpsc::SendError> + Unpin),
+    ) -> Result<()> {
+        let id = self.next_ping_id;
+        self.next_ping_id = self.next_ping_id.wrapping_add(1);
+
+        self.pending_pings.push_back((id, timestamp));
+        if self.pending_pings.len() > MAX_PENDING_PINGS {
+            self.pending_pings.pop_front();
+        }
+
+        subscribe_tx
+            .send(SubscribeRequest {
+                ping: Some(SubscribeRequestPing { id }),
+                ..Default::default()
+            })
+            .await?;
+        Ok(())
+    }
+
     pub async fn handle_message(
         &mut self,
         msg: SubscribeUpdate,
         receive_timestamp: u128,
+        created_at: Option<u128>,
         subscribe_tx: &mut (impl SinkExt<SubscribeRequest, Error = futures::channel::mpsc::SendError> + Unpin),
     ) -> Result<()> {
         match msg.update_oneof {
             Some(subscribe_update::UpdateOneof::Slot(slot_update)) => {
-                self.update_handlers.handle_slot_update(slot_update, receive_timestamp);
+                self.record_slot(slot_update.slot);
+                self.update_handlers.handle_slot_update(slot_update, &msg.filters, receive_timestamp, created_at);
             }
             Some(subscribe_update::UpdateOneof::Account(account_update)) => {
-                self.update_handlers.handle_account_update(account_update);
+                self.record_slot(account_update.slot);
+                self.update_handlers.handle_account_update(account_update, receive_timestamp, created_at);
             }
             Some(subscribe_update::UpdateOneof::Transaction(tx_update)) => {
-                self.update_handlers.handle_transaction_update(tx_update);
+                self.record_slot(tx_update.slot);
+                self.update_handlers.handle_transaction_update(tx_update, receive_timestamp, created_at);
             }
             Some(subscribe_update::UpdateOneof::Block(block_update)) => {
-                self.update_handlers.handle_block_update(block_update);
+                self.record_slot(block_update.slot);
+                self.update_handlers.handle_block_update(block_update, receive_timestamp, created_at);
+            }
+            Some(subscribe_update::UpdateOneof::BlockMeta(block_meta_update)) => {
+                self.record_slot(block_meta_update.slot);
+                self.update_handlers.handle_block_meta_update(block_meta_update, receive_timestamp, created_at);
             }
             Some(subscribe_update::UpdateOneof::Ping(_ping)) => {
                 info!("[{}] Received ping from server - replying to keep connection alive", self.stream_id);
-                subscribe_tx
-                    .send(SubscribeRequest {
-                        ping: Some(SubscribeRequestPing { id: 1 }),
-                        ..Default::default()
-                    })
-                    .await?;
+                self.send_ping(receive_timestamp, subscribe_tx).await?;
             }
             Some(subscribe_update::UpdateOneof::Pong(pong)) => {
-                info!("[{}] Received pong response with id: {}", self.stream_id, pong.id);
+                match self.pending_pings.iter().position(|(id, _)| *id == pong.id) {
+                    Some(index) => {
+                        let (_, sent_at) = self.pending_pings.remove(index).unwrap();
+                        self.referee.send_pong(self.stream_id.clone(), receive_timestamp.saturating_sub(sent_at));
+                    }
+                    None => debug!("[{}] Received pong for unknown ping id: {}", self.stream_id, pong.id),
+                }
             }
             None => {
                 error!("[{}] update not found in the message", self.stream_id);
@@ -1,6 +1,6 @@
-use tracing::info;
+use tracing::{debug, info};
 use yellowstone_grpc_proto::prelude::*;
-use crate::referee::SharedReferee;
+use crate::referee::{RaceKey, RaceKind, SharedReferee};
 
 pub struct UpdateHandlers {
     stream_id: String,
@@ -12,7 +12,7 @@ impl UpdateHandlers {
         Self { stream_id, referee }
     }
 
-    pub fn handle_slot_update(&self, slot_update: SubscribeUpdateSlot, receive_timestamp: u128) {
+    pub fn handle_slot_update(&self, slot_update: SubscribeUpdateSlot, filters: &[String], receive_timestamp: u128, created_at: Option<u128>) {
         // Convert nanoseconds to milliseconds for display
         let timestamp_ms = receive_timestamp / 1_000_000;
         
@@ -26,26 +26,65 @@ impl UpdateHandlers {
             receive_timestamp
         );
         
+        // The same update can match both the slot and the slot_status filters
+        let matches_filter = |kind: RaceKind| filters.iter().any(|f| *f == kind.to_string());
+
         // Non-blocking send to the event channel
         // No more tokio::spawn or mutex lock!
-        self.referee.send_slot(
-            slot_update.slot,
-            self.stream_id.clone(),
-            receive_timestamp
-        );
+        if matches_filter(RaceKind::Slot) {
+            self.referee.send_slot(
+                slot_update.slot,
+                self.stream_id.clone(),
+                receive_timestamp,
+                created_at
+            );
+        }
+
+        if matches_filter(RaceKind::SlotStatus) {
+            self.referee.send_report(
+                RaceKey::SlotStatus { slot: slot_update.slot, status: slot_update.status() },
+                self.stream_id.clone(),
+                receive_timestamp,
+                created_at
+            );
+        }
     }
 
-    pub fn handle_account_update(&self, account_update: SubscribeUpdateAccount) {
-        info!(
-            "[{}] Account update: pubkey={}, slot={}, lamports={}",
+    pub fn handle_account_update(&self, account_update: SubscribeUpdateAccount, receive_timestamp: u128, created_at: Option<u128>) {
+        let account = match &account_update.account {
+            Some(account) => account,
+            None => {
+                info!("[{}] Account update with no account info", self.stream_id);
+                return;
+            }
+        };
+
+        let pubkey = bs58::encode(&account.pubkey).into_string();
+
+        debug!(
+            "[{}] Account update: pubkey={}, slot={}, write_version={}, lamports={}, received_at={}ns",
             self.stream_id,
-            bs58::encode(&account_update.account.as_ref().unwrap().pubkey).into_string(),
+            pubkey,
             account_update.slot,
-            account_update.account.as_ref().unwrap().lamports
+            account.write_version,
+            account.lamports,
+            receive_timestamp
+        );
+
+        // Every distinct (pubkey, slot, write_version) is its own race
+        self.referee.send_report(
+            RaceKey::Account {
+                pubkey,
+                slot: account_update.slot,
+                write_version: account.write_version,
+            },
+            self.stream_id.clone(),
+            receive_timestamp,
+            created_at
         );
     }
 
-    pub fn handle_transaction_update(&self, tx_update: SubscribeUpdateTransaction) {
+    pub fn handle_transaction_update(&self, tx_update: SubscribeUpdateTransaction, receive_timestamp: u128, created_at: Option<u128>) {
         // Get the actual transaction from inside the update
         let tx_info = match &tx_update.transaction {
             Some(info) => info,
@@ -55,61 +94,83 @@ impl UpdateHandlers {
             }
         };
 
-        // Get the actual transaction
-        let tx = match &tx_info.transaction {
-            Some(tx) => tx,
-            None => {
-                info!("[{}] Transaction update with no transaction data", self.stream_id);
-                return;
-            }
-        };
-
-        // Get the message which contains account_keys and instructions
-        let message = match &tx.message {
-            Some(msg) => msg,
-            None => {
-                info!("[{}] Transaction update with no message", self.stream_id);
-                return;
-            }
-        };
+        let signature = bs58::encode(&tx_info.signature).into_string();
 
-        // Basic transaction info
-        info!(
-            "[{}] Transaction update: signature={}, slot={}",
+        // Basic transaction info - the referee logs the race position for every transaction,
+        // so the details are only emitted at debug level
+        debug!(
+            "[{}] Transaction update: signature={}, slot={}, received_at={}ns",
             self.stream_id,
-            bs58::encode(&tx_info.signature).into_string(),
-            tx_update.slot
+            signature,
+            tx_update.slot,
+            receive_timestamp
         );
 
         // Log number of accounts and instructions
-        info!(
-            "[{}]   Accounts: {}, Instructions: {}",
-            self.stream_id,
-            message.account_keys.len(),
-            message.instructions.len()
-        );
+        if let Some(message) = tx_info.transaction.as_ref().and_then(|tx| tx.message.as_ref()) {
+            debug!(
+                "[{}]   Accounts: {}, Instructions: {}",
+                self.stream_id,
+                message.account_keys.len(),
+                message.instructions.len()
+            );
+        }
 
         // Log if transaction failed
         if let Some(meta) = &tx_info.meta {
             if let Some(err) = &meta.err {
-                info!("[{}]   Status: FAILED - {:?}", self.stream_id, err);
+                debug!("[{}]   Status: FAILED - {:?}", self.stream_id, err);
             } else {
-                info!("[{}]   Status: SUCCESS", self.stream_id);
+                debug!("[{}]   Status: SUCCESS", self.stream_id);
             }
             
             // Log compute units used
             if let Some(compute_units) = meta.compute_units_consumed {
-                info!("[{}]   Compute units: {}", self.stream_id, compute_units);
+                debug!("[{}]   Compute units: {}", self.stream_id, compute_units);
             }
         }
+
+        // Race on the first delivery of each signature
+        self.referee.send_report(
+            RaceKey::Transaction { signature, slot: tx_update.slot },
+            self.stream_id.clone(),
+            receive_timestamp,
+            created_at
+        );
     }
 
-    pub fn handle_block_update(&self, block_update: SubscribeUpdateBlock) {
+    pub fn handle_block_update(&self, block_update: SubscribeUpdateBlock, receive_timestamp: u128, created_at: Option<u128>) {
         info!(
-            "[{}] Block update: slot={}, blockhash={}",
+            "[{}] Block update: slot={}, blockhash={}, transactions={}, received_at={}ns",
             self.stream_id,
             block_update.slot,
-            bs58::encode(&block_update.blockhash).into_string()
+            block_update.blockhash,
+            block_update.executed_transaction_count,
+            receive_timestamp
+        );
+
+        self.referee.send_report(
+            RaceKey::Block { slot: block_update.slot, blockhash: block_update.blockhash },
+            self.stream_id.clone(),
+            receive_timestamp,
+            created_at
+        );
+    }
+
+    pub fn handle_block_meta_update(&self, block_meta_update: SubscribeUpdateBlockMeta, receive_timestamp: u128, created_at: Option<u128>) {
+        info!(
+            "[{}] Block meta update: slot={}, blockhash={}, received_at={}ns",
+            self.stream_id,
+            block_meta_update.slot,
+            block_meta_update.blockhash,
+            receive_timestamp
+        );
+
+        self.referee.send_report(
+            RaceKey::BlockMeta { slot: block_meta_update.slot, blockhash: block_meta_update.blockhash },
+            self.stream_id.clone(),
+            receive_timestamp,
+            created_at
         );
     }
 }
\ No newline at end of file
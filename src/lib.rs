@@ -0,0 +1,64 @@
+//! Race Solana data streams - Yellowstone gRPC, PubSub WebSockets, raw shreds or
+//! recorded races - against each other and measure which delivers updates first.
+//!
+//! The `gRPC_speedrace` binary is a thin wrapper around this crate. To race from
+//! your own service, build a [`Config`], [`Race::start`] it and read results
+//! through its [`Referee`]. Subscribe through a [`Race::builder`] to see every
+//! race from the first connection on:
+//!
+//! ```no_run
+//! use grpc_speedrace::{Config, Race, StreamConfig, StreamKind};
+//!
+//! # async fn run() -> anyhow::Result<()> {
+//! let config = Config {
+//!     max_slots: 100,
+//!     stop_at_max: true,
+//!     streams: vec![
+//!         StreamConfig::new("Provider A", StreamKind::Grpc, "https://grpc.provider-a.com"),
+//!         StreamConfig::new("Provider B", StreamKind::Grpc, "https://grpc.provider-b.com"),
+//!     ],
+//!     ..Default::default()
+//! };
+//!
+//! let builder = Race::builder(config)?;
+//! let mut closed = builder.referee().subscribe().await;
+//! let mut race = builder.start().await?;
+//! tokio::spawn(async move {
+//!     while let Some(closed) = closed.recv().await {
+//!         println!("{} won by {}", closed.result.key, closed.result.winner);
+//!     }
+//! });
+//!
+//! race.wait().await;
+//! for summary in race.referee().summarize().await {
+//!     for stream in &summary.streams {
+//!         println!("{} {}: {} wins", summary.race, stream.name, stream.wins);
+//!     }
+//! }
+//! race.shutdown().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::sync::Arc;
+
+mod client;
+mod handlers;
+
+pub mod clock;
+pub mod config;
+pub mod export;
+pub mod metrics;
+pub mod output;
+pub mod race;
+pub mod referee;
+pub mod sources;
+
+pub use config::{Config, StreamConfig, StreamKind};
+pub use race::{Race, RaceBuilder, RaceEnd};
+pub use referee::{RaceClosed, RaceEvent, RaceKey, RaceKind, RaceSummary, Referee, SharedReferee, StreamMetrics};
+
+use clock::RaceClock;
+
+// Shared clock reference for all streams - ensures consistent timing
+pub type SharedClock = Arc<RaceClock>;
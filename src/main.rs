@@ -1,145 +1,67 @@
 use anyhow::Result;
-use backoff::{future::retry, ExponentialBackoff};
-use futures::TryFutureExt;
-use tracing::{error, info};
-use tokio::task::JoinHandle;
-use std::sync::Arc;
-use std::time::Instant;
+use clap::Parser;
+use tracing::info;
+use std::process::ExitCode;
 
-mod client;
-mod config;
-mod handlers;
-mod subscription;
-mod referee;
+use grpc_speedrace::config::Config;
+use grpc_speedrace::output::STDOUT_PATH;
+use grpc_speedrace::{Race, RaceEnd};
 
-use client::GrpcClient;
-use config::{Config, StreamConfig};
-use subscription::SubscriptionManager;
-use referee::{Referee, SharedReferee, RaceEvent};
-use yellowstone_grpc_proto::prelude::CommitmentLevel;
+mod cli;
 
-// Shared clock reference for all streams - ensures consistent timing
-pub type SharedClock = Arc<Instant>;
+use cli::Cli;
 
 #[tokio::main]
-async fn main() -> Result<()> {
-    // Initialize logging
-    tracing_subscriber::fmt::init();
+async fn main() -> Result<ExitCode> {
+    let cli = Cli::parse();
     
-    let config = Config::from_file()?;
+    let mut config = Config::from_file(&cli.config)?;
+    cli.apply_overrides(&mut config)?;
     
-    info!("Starting gRPC subscription comparison with {} streams", config.streams.len());
-    for (i, stream) in config.streams.iter().enumerate() {
-        info!("Stream {}: {} - {}", i + 1, stream.name, stream.endpoint);
+    // Initialize logging - keep stdout clean when JSON summaries are written there
+    if config.output.json_path.as_deref() == Some(STDOUT_PATH) {
+        tracing_subscriber::fmt().with_writer(std::io::stderr).init();
+    } else {
+        tracing_subscriber::fmt::init();
     }
     
-    // Create the referee with event channel
-    let (referee, event_rx) = Referee::new(config.max_slots, config.stop_at_max, config.warmup_slots);
+    info!("Loaded configuration from {}", cli.config.display());
     
-    // Create a shared high-resolution clock reference
-    let shared_clock: SharedClock = Arc::new(Instant::now());
+    let mut race = Race::start(config).await?;
     
-    let commitment = config.commitment_level()?;
-
-    info!("Race configuration:");
-    info!("  Max slots: {}", config.max_slots);
-    info!("  Stop at max: {}", config.stop_at_max);
-    info!("  Commitment level: {}", config.commitment);
-    info!("  Warmup slots: {}", config.warmup_slots);
-    
-    // Spawn the event processor that handles all race events in order
-    let processor_referee = referee.clone();
-    let event_processor_handle = tokio::spawn(async move {
-        let mut rx = event_rx;
-        
-        while let Some(event) = rx.recv().await {
-            match event {
-                RaceEvent::SlotReport { slot, stream_id, timestamp } => {
-                    let should_continue = processor_referee.process_slot_report(slot, stream_id, timestamp).await;
-                    
-                    // If race is complete, exit the entire program
-                    if !should_continue && processor_referee.is_complete().await {
-                        info!("Race complete! Maximum slots reached.");
-                        processor_referee.print_summary().await;
-                        std::process::exit(0);
-                    }
-                }
-            }
-        }
-        info!("Event processor shutting down");
-    });
-    
-    // Create subscription tasks for all streams
-    let mut subscriptions: Vec<JoinHandle<Result<()>>> = Vec::new();
-    
-    for stream_config in config.streams {
-        let referee_clone = referee.clone();
-        let clock_clone = shared_clock.clone();
-        let subscription = tokio::spawn(run_subscription(stream_config, referee_clone, clock_clone, commitment));
-        subscriptions.push(subscription);
-    }
-    
-    // Spawn a task to periodically print summaries
-    let summary_referee = referee.clone();
-    tokio::spawn(async move {
-        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(30));
-        loop {
-            interval.tick().await;
-            summary_referee.print_summary().await;
-            
-            // Check if race is complete
-            if summary_referee.is_complete().await {
-                break;
-            }
-        }
-    });
-    
-    // Wait for all subscriptions
-    let results = futures::future::join_all(subscriptions).await;
-    
-    // Handle results
-    for (i, result) in results.into_iter().enumerate() {
-        if let Err(e) = result {
-            error!("Stream {} task failed: {}", i + 1, e);
+    // Run until the race completes, a shutdown signal arrives, or every stream has given up
+    let exit_code = tokio::select! {
+        end = race.wait() => match end {
+            RaceEnd::Complete | RaceEnd::StreamsEnded { failed: false } => ExitCode::SUCCESS,
+            RaceEnd::StreamsEnded { failed: true } | RaceEnd::Failed => ExitCode::FAILURE,
+        },
+        signal = shutdown_signal() => {
+            let (name, code) = signal?;
+            info!("Received {}, shutting down", name);
+            ExitCode::from(code)
         }
-    }
+    };
     
-    // Wait for event processor to finish
-    let _ = event_processor_handle.await;
+    race.shutdown().await;
     
-    Ok(())
+    Ok(exit_code)
 }
 
-async fn run_subscription(config: StreamConfig, referee: SharedReferee, clock: SharedClock, commitment: CommitmentLevel) -> Result<()> {
-    retry(ExponentialBackoff::default(), move || {
-        let config = config.clone();
-        let stream_name = config.name.clone();
-        let stream_name_for_error = stream_name.clone();
-        let referee = referee.clone();
-        let clock = clock.clone();
-
-        async move {
-            info!("[{}] Connecting to gRPC endpoint: {}", stream_name, config.endpoint);
-
-            // Create client
-            let client = GrpcClient::new(config)
-                .connect()
-                .await
-                .map_err(|e| backoff::Error::transient(e))?;
-
-            info!("[{}] Successfully connected to Yellowstone gRPC", stream_name);
+// Wait for SIGINT or SIGTERM, returning the signal's name and the conventional 128 + signal number exit status
+#[cfg(unix)]
+async fn shutdown_signal() -> Result<(&'static str, u8)> {
+    use tokio::signal::unix::{signal, SignalKind};
 
-            // Run the subscription with shared clock
-            let mut subscription_manager = SubscriptionManager::new(client, stream_name.clone(), referee, clock, commitment);
-            subscription_manager
-                .run()
-                .await
-                .map_err(|e| backoff::Error::transient(e))?;
+    let mut interrupt = signal(SignalKind::interrupt())?;
+    let mut terminate = signal(SignalKind::terminate())?;
+    tokio::select! {
+        _ = interrupt.recv() => Ok(("SIGINT", 130)),
+        _ = terminate.recv() => Ok(("SIGTERM", 143)),
+    }
+}
 
-            Ok::<(), backoff::Error<anyhow::Error>>(())
-        }
-        .inspect_err(move |error| error!("[{}] Connection failed, will retry: {error}", stream_name_for_error))
-    })
-    .await
-    .map_err(Into::into)
-}
\ No newline at end of file
+#[cfg(not(unix))]
+async fn shutdown_signal() -> Result<(&'static str, u8)> {
+    tokio::signal::ctrl_c().await?;
+    Ok(("Ctrl-C", 130))
+}
@@ -0,0 +1,228 @@
+use anyhow::Result;
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry, TextEncoder,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+use crate::referee::RaceClosed;
+
+// Time-behind-leader buckets in seconds (0.5ms .. 2.5s)
+const TIME_BEHIND_BUCKETS: [f64; 12] = [
+    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+];
+
+// Prometheus metrics fed by the referee
+pub struct Metrics {
+    registry: Registry,
+    wins: IntCounterVec,
+    reports: IntCounterVec,
+    reconnects: IntCounterVec,
+    stream_errors: IntCounterVec,
+    stalls: IntCounterVec,
+    duplicates: IntCounterVec,
+    reorders: IntCounterVec,
+    late_reports: IntCounterVec,
+    races_closed: IntCounterVec,
+    time_behind: HistogramVec,
+    current_slot: IntGaugeVec,
+    connected: IntGaugeVec,
+}
+
+impl Metrics {
+    pub fn new() -> Result<Self> {
+        let registry = Registry::new_custom(Some("speedrace".to_string()), None)?;
+
+        let wins = IntCounterVec::new(
+            Opts::new("wins_total", "Races won by each stream"),
+            &["race", "stream"],
+        )?;
+        let reports = IntCounterVec::new(
+            Opts::new("reports_total", "Updates reported by each stream"),
+            &["race", "stream"],
+        )?;
+        let reconnects = IntCounterVec::new(
+            Opts::new("reconnects_total", "Successful reconnects after the first connection"),
+            &["stream"],
+        )?;
+        let stream_errors = IntCounterVec::new(
+            Opts::new("stream_errors_total", "Connection and stream errors"),
+            &["stream"],
+        )?;
+        let stalls = IntCounterVec::new(
+            Opts::new("stalls_total", "Reconnects forced by the watchdog because a stream fell behind the leader"),
+            &["stream"],
+        )?;
+        let duplicates = IntCounterVec::new(
+            Opts::new("duplicates_total", "Repeat deliveries of an update the stream already reported"),
+            &["race", "stream"],
+        )?;
+        let reorders = IntCounterVec::new(
+            Opts::new("reorders_total", "Slots delivered below the stream's previous highest slot"),
+            &["stream"],
+        )?;
+        let late_reports = IntCounterVec::new(
+            Opts::new("late_reports_total", "Reports that arrived after their race had closed"),
+            &["race", "stream"],
+        )?;
+        let races_closed = IntCounterVec::new(
+            Opts::new("races_closed_total", "Closed races by why they closed"),
+            &["race", "reason"],
+        )?;
+        let time_behind = HistogramVec::new(
+            HistogramOpts::new("time_behind_leader_seconds", "Time each stream finished behind the race winner")
+                .buckets(TIME_BEHIND_BUCKETS.to_vec()),
+            &["race", "stream"],
+        )?;
+        let current_slot = IntGaugeVec::new(
+            Opts::new("current_slot", "Highest slot reported by each stream"),
+            &["stream"],
+        )?;
+
+        registry.register(Box::new(wins.clone()))?;
+        registry.register(Box::new(reports.clone()))?;
+        registry.register(Box::new(reconnects.clone()))?;
+        registry.register(Box::new(stream_errors.clone()))?;
+        registry.register(Box::new(stalls.clone()))?;
+        registry.register(Box::new(duplicates.clone()))?;
+        registry.register(Box::new(reorders.clone()))?;
+        registry.register(Box::new(late_reports.clone()))?;
+        registry.register(Box::new(races_closed.clone()))?;
+        registry.register(Box::new(time_behind.clone()))?;
+        let connected = IntGaugeVec::new(
+            Opts::new("connected", "Whether each stream is currently connected (1) or not (0)"),
+            &["stream"],
+        )?;
+
+        registry.register(Box::new(current_slot.clone()))?;
+        registry.register(Box::new(connected.clone()))?;
+
+        Ok(Self {
+            registry,
+            wins,
+            reports,
+            reconnects,
+            stream_errors,
+            stalls,
+            duplicates,
+            reorders,
+            late_reports,
+            races_closed,
+            time_behind,
+            current_slot,
+            connected,
+        })
+    }
+
+    pub fn record_report(&self, race: &str, stream_id: &str, slot: u64) {
+        self.reports.with_label_values(&[race, stream_id]).inc();
+
+        let gauge = self.current_slot.with_label_values(&[stream_id]);
+        if slot as i64 > gauge.get() {
+            gauge.set(slot as i64);
+        }
+    }
+
+    // Wins and latencies are only recorded once a race closes, since a later
+    // report with an earlier timestamp can still change the winner
+    pub fn record_closed(&self, closed: &RaceClosed) {
+        let result = &closed.result;
+        let race = result.key.kind().to_string();
+
+        self.races_closed.with_label_values(&[&race, &closed.reason.to_string()]).inc();
+        self.wins.with_label_values(&[&race, &result.winner]).inc();
+
+        for (stream_id, timestamp) in &result.finish_times {
+            let time_behind_ns = timestamp.saturating_sub(result.winner_timestamp);
+            self.time_behind
+                .with_label_values(&[&race, stream_id])
+                .observe(time_behind_ns as f64 / 1_000_000_000.0);
+        }
+    }
+
+    pub fn record_reconnect(&self, stream_id: &str) {
+        self.reconnects.with_label_values(&[stream_id]).inc();
+    }
+
+    pub fn record_stream_error(&self, stream_id: &str) {
+        self.stream_errors.with_label_values(&[stream_id]).inc();
+    }
+
+    pub fn record_stall(&self, stream_id: &str) {
+        self.stalls.with_label_values(&[stream_id]).inc();
+    }
+
+    pub fn record_connection_up(&self, stream_id: &str, up: bool) {
+        self.connected.with_label_values(&[stream_id]).set(up as i64);
+    }
+
+    pub fn record_duplicate(&self, race: &str, stream_id: &str) {
+        self.duplicates.with_label_values(&[race, stream_id]).inc();
+    }
+
+    pub fn record_reorder(&self, stream_id: &str) {
+        self.reorders.with_label_values(&[stream_id]).inc();
+    }
+
+    pub fn record_late(&self, race: &str, stream_id: &str) {
+        self.late_reports.with_label_values(&[race, stream_id]).inc();
+    }
+
+    pub fn encode(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8(buffer)?)
+    }
+}
+
+// Record races as they close, until the referee shuts down
+pub async fn record_closed_races(metrics: Arc<Metrics>, mut closed_rx: mpsc::UnboundedReceiver<RaceClosed>) {
+    while let Some(closed) = closed_rx.recv().await {
+        metrics.record_closed(&closed);
+    }
+}
+
+// Minimal HTTP server exposing GET /metrics for Prometheus scrapes
+pub async fn serve(metrics: Arc<Metrics>, addr: SocketAddr) -> Result<()> {
+    let listener = TcpListener::bind(addr).await
+        .map_err(|e| anyhow::anyhow!("Failed to bind metrics endpoint on {}: {}", addr, e))?;
+
+    info!("Serving Prometheus metrics on http://{}/metrics", addr);
+
+    loop {
+        let (mut socket, _) = listener.accept().await?;
+        let metrics = metrics.clone();
+
+        tokio::spawn(async move {
+            let mut buffer = [0u8; 1024];
+            let read = match socket.read(&mut buffer).await {
+                Ok(read) => read,
+                Err(_) => return,
+            };
+            let request = String::from_utf8_lossy(&buffer[..read]);
+
+            let response = if request.starts_with("GET /metrics ") {
+                match metrics.encode() {
+                    Ok(body) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    Err(e) => {
+                        error!("Failed to encode metrics: {}", e);
+                        "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                    }
+                }
+            } else {
+                "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+            };
+
+            let _ = socket.write_all(response.as_bytes()).await;
+            let _ = socket.shutdown().await;
+        });
+    }
+}
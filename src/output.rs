@@ -0,0 +1,57 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, Write};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::referee::{ConnectionSummary, RaceSummary, Referee};
+
+// Path value that sends JSON summaries to stdout instead of a file
+pub const STDOUT_PATH: &str = "-";
+
+// One JSON line per periodic or final summary
+#[derive(Serialize)]
+struct SummaryRecord<'a> {
+    timestamp_ms: u128,
+    #[serde(rename = "final")]
+    is_final: bool,
+    races: &'a [RaceSummary],
+    connections: &'a [ConnectionSummary],
+}
+
+pub struct JsonSummaryWriter {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonSummaryWriter {
+    pub fn new(path: &str) -> Result<Self> {
+        let writer: Box<dyn Write + Send> = if path == STDOUT_PATH {
+            Box::new(io::stdout())
+        } else {
+            let file = File::create(path)
+                .map_err(|e| anyhow::anyhow!("Failed to create JSON output {}: {}", path, e))?;
+            Box::new(file)
+        };
+
+        Ok(Self { writer: Mutex::new(writer) })
+    }
+
+    pub async fn write_summary(&self, referee: &Referee, is_final: bool) -> Result<()> {
+        let races = referee.summarize().await;
+        let connections = referee.connection_summaries().await;
+
+        let record = SummaryRecord {
+            timestamp_ms: SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis(),
+            is_final,
+            races: &races,
+            connections: &connections,
+        };
+        let line = serde_json::to_string(&record)?;
+
+        let mut writer = self.writer.lock().unwrap();
+        writeln!(writer, "{}", line)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
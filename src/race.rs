@@ -0,0 +1,353 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, watch};
+use tokio::task::{AbortHandle, JoinHandle};
+use tracing::{error, info};
+
+use crate::clock::RaceClock;
+use crate::config::{Config, StreamKind};
+use crate::export::RaceExporter;
+use crate::metrics::{self, Metrics};
+use crate::output::JsonSummaryWriter;
+use crate::referee::{RaceEvent, Referee, SharedReferee};
+use crate::sources::{self, SlotSource, SourceContext, SourceFactory};
+use crate::SharedClock;
+
+// How often races whose deadline passed are closed, even when no reports are arriving
+const DEADLINE_TICK: Duration = Duration::from_millis(100);
+
+// How often progress summaries are printed and written
+const SUMMARY_INTERVAL: Duration = Duration::from_secs(30);
+
+// How a race ended on its own
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaceEnd {
+    // Every enabled race kind tracked max_slots races (stop_at_max)
+    Complete,
+    // Every stream ended - `failed` when any of them gave up reconnecting
+    StreamsEnded { failed: bool },
+    // The event processor panicked - the results stop where it did
+    Failed,
+}
+
+// A race that hasn't started yet
+//
+// Its referee already exists, so subscriptions made through `referee()` see every event and
+// closed race from the first connection attempt on. `start` connects the streams.
+pub struct RaceBuilder {
+    config: Config,
+    referee: SharedReferee,
+    event_rx: mpsc::UnboundedReceiver<RaceEvent>,
+    metrics: Option<Arc<Metrics>>,
+    // Sources of the custom streams, by stream name
+    sources: HashMap<String, SourceFactory>,
+}
+
+// A running race over the configured streams
+//
+// `start` connects every stream, `referee()` gives access to events and metrics while it runs,
+// `wait` returns once it ends on its own and `shutdown` closes open races and writes the final
+// summary and exports.
+pub struct Race {
+    referee: SharedReferee,
+    json_writer: Option<Arc<JsonSummaryWriter>>,
+    processor: JoinHandle<()>,
+    // Whether any stream gave up
+    streams: JoinHandle<bool>,
+    export: Option<JoinHandle<()>>,
+    // Streams and periodic tasks, cancelled on shutdown
+    tasks: Vec<AbortHandle>,
+    // Tells the event processor to drain what is queued and stop
+    shutdown_tx: watch::Sender<bool>,
+    ended: Option<RaceEnd>,
+}
+
+impl RaceBuilder {
+    // The referee the race will report to - subscribe here to miss nothing
+    pub fn referee(&self) -> &SharedReferee {
+        &self.referee
+    }
+
+    // Race the custom stream named `stream` with the source `factory` builds
+    //
+    // The factory is called once the race starts, with the context the source reports through.
+    // `run_source` keeps it connected like any built-in kind.
+    pub fn source<F, S>(mut self, stream: impl Into<String>, factory: F) -> Self
+    where
+        F: FnOnce(SourceContext) -> S + Send + 'static,
+        S: SlotSource + 'static,
+    {
+        self.sources.insert(stream.into(), Box::new(move |context| Box::new(factory(context))));
+        self
+    }
+
+    // Every custom stream needs a source, and every source a custom stream
+    fn check_sources(&self) -> Result<()> {
+        for name in self.sources.keys() {
+            match self.config.streams.iter().find(|s| &s.name == name) {
+                None => return Err(anyhow::anyhow!("Source registered for unknown stream '{}'", name)),
+                Some(stream) if stream.kind != StreamKind::Custom => {
+                    return Err(anyhow::anyhow!(
+                        "Source registered for {} stream '{}' - only custom streams take a registered source",
+                        stream.kind,
+                        name
+                    ));
+                }
+                Some(_) => {}
+            }
+        }
+        if let Some(stream) = self.config.streams.iter().find(|s| s.kind == StreamKind::Custom && !self.sources.contains_key(&s.name)) {
+            return Err(anyhow::anyhow!("No source registered for custom stream '{}'", stream.name));
+        }
+        Ok(())
+    }
+
+    // Start racing the configured streams
+    pub async fn start(self) -> Result<Race> {
+        self.check_sources()?;
+        let RaceBuilder { config, referee, mut event_rx, metrics, sources: mut custom_sources } = self;
+
+        info!("Starting gRPC subscription comparison with {} streams", config.streams.len());
+        for (i, stream) in config.streams.iter().enumerate() {
+            info!("Stream {}: {} - {} ({})", i + 1, stream.name, stream.endpoint, stream.kind);
+        }
+
+        // Optional export of raw finish times, one column pair per configured stream
+        let exporter = match &config.output.export_path {
+            Some(path) => {
+                let stream_names = config.streams.iter().map(|s| s.name.clone()).collect();
+                Some(RaceExporter::new(path, config.output.export_format, stream_names)?)
+            }
+            None => None,
+        };
+
+        let mut tasks = Vec::new();
+
+        // Optional Prometheus endpoint
+        if let (Some(metrics), Some(listen)) = (&metrics, &config.metrics.listen) {
+            let addr = listen.parse()?;
+            let server_metrics = metrics.clone();
+            let server = tokio::spawn(async move {
+                if let Err(e) = metrics::serve(server_metrics, addr).await {
+                    error!("Metrics endpoint stopped: {}", e);
+                }
+            });
+            tasks.push(server.abort_handle());
+        }
+
+        // Exporter and metrics consume races as they close
+        let export = match exporter {
+            Some(exporter) => Some(tokio::spawn(exporter.run(referee.subscribe().await))),
+            None => None,
+        };
+        if let Some(metrics) = metrics {
+            tokio::spawn(metrics::record_closed_races(metrics, referee.subscribe().await));
+        }
+
+        // Optional machine-readable summary output
+        let json_writer = match &config.output.json_path {
+            Some(path) => Some(Arc::new(JsonSummaryWriter::new(path)?)),
+            None => None,
+        };
+
+        // Create a shared high-resolution clock reference
+        let shared_clock: SharedClock = Arc::new(RaceClock::new());
+
+        let request = config.subscribe_request()?;
+
+        info!("Race configuration:");
+        info!("  Max slots: {}", config.max_slots);
+        info!("  Stop at max: {}", config.stop_at_max);
+        info!("  Commitment level: {}", config.commitment);
+        info!("  Warmup slots: {}", config.warmup_slots);
+        info!("  Race deadline: {}ms", config.race_deadline_ms);
+        info!("  Resume from last slot: {}", config.resume_from_last_slot);
+        info!("  Races: {}", config.races.iter().map(|r| r.to_string()).collect::<Vec<_>>().join(", "));
+
+        // Spawn the event processor that handles all race events in order
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(false);
+        let processor_referee = referee.clone();
+        let processor = tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(event) = event_rx.recv() => {
+                        if processor_referee.process_event(event).await {
+                            info!("Race complete! Maximum races reached.");
+                            break;
+                        }
+                    }
+                    _ = shutdown_rx.changed() => {
+                        // Streams are cancelled by now - finish whatever they already reported
+                        while let Ok(event) = event_rx.try_recv() {
+                            processor_referee.process_event(event).await;
+                        }
+                        break;
+                    }
+                }
+            }
+            info!("Event processor shutting down");
+        });
+
+        // Create subscription tasks for all streams
+        let mut subscriptions = Vec::new();
+        for stream_config in &config.streams {
+            let context = SourceContext::new(stream_config.name.clone(), referee.clone(), shared_clock.clone());
+            let source = match custom_sources.remove(&stream_config.name) {
+                Some(factory) => factory(context.clone()),
+                None => sources::from_config(stream_config, &request, context.clone())?,
+            };
+            let subscription = tokio::spawn(sources::run_source(
+                source,
+                context,
+                config.reconnect_policy(stream_config),
+                config.resume_from_last_slot,
+            ));
+            tasks.push(subscription.abort_handle());
+            subscriptions.push(subscription);
+        }
+
+        let streams = tokio::spawn(async move {
+            let mut failed = false;
+            for (i, result) in futures::future::join_all(subscriptions).await.into_iter().enumerate() {
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => {
+                        error!("Stream {} gave up: {}", i + 1, e);
+                        failed = true;
+                    }
+                    Err(e) => {
+                        error!("Stream {} task failed: {}", i + 1, e);
+                        failed = true;
+                    }
+                }
+            }
+            failed
+        });
+
+        let deadline_referee = referee.clone();
+        let deadline_clock = shared_clock.clone();
+        let deadline = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(DEADLINE_TICK);
+            loop {
+                interval.tick().await;
+                deadline_referee.send_tick(deadline_clock.timestamp(Instant::now()));
+            }
+        });
+        tasks.push(deadline.abort_handle());
+
+        // Spawn a task to periodically print summaries
+        let summary_referee = referee.clone();
+        let summary_json_writer = json_writer.clone();
+        let summary = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(SUMMARY_INTERVAL);
+            loop {
+                interval.tick().await;
+                summary_referee.print_summary().await;
+                if let Some(writer) = &summary_json_writer {
+                    if let Err(e) = writer.write_summary(&summary_referee, false).await {
+                        error!("Failed to write JSON summary: {}", e);
+                    }
+                }
+
+                // Check if race is complete
+                if summary_referee.is_complete().await {
+                    break;
+                }
+            }
+        });
+        tasks.push(summary.abort_handle());
+
+        Ok(Race {
+            referee,
+            json_writer,
+            processor,
+            streams,
+            export,
+            tasks,
+            shutdown_tx,
+            ended: None,
+        })
+    }
+}
+
+impl Race {
+    // Validate the config and start racing its streams
+    pub async fn start(config: Config) -> Result<Self> {
+        Self::builder(config)?.start().await
+    }
+
+    // Validate the config and set up its referee, without connecting any stream yet
+    pub fn builder(config: Config) -> Result<RaceBuilder> {
+        config.validate()?;
+
+        let metrics = match &config.metrics.listen {
+            Some(_) => Some(Arc::new(Metrics::new()?)),
+            None => None,
+        };
+        let (referee, event_rx) = Referee::new(&config, metrics.clone());
+
+        Ok(RaceBuilder {
+            config,
+            referee,
+            event_rx,
+            metrics,
+            sources: HashMap::new(),
+        })
+    }
+
+    // The race's referee - subscribe to events and closed races, or read metrics, through it
+    pub fn referee(&self) -> &SharedReferee {
+        &self.referee
+    }
+
+    // Wait until the race completes or every stream has ended
+    pub async fn wait(&mut self) -> RaceEnd {
+        if let Some(end) = self.ended {
+            return end;
+        }
+
+        let end = tokio::select! {
+            processed = &mut self.processor => match processed {
+                Ok(()) => RaceEnd::Complete,
+                Err(e) => {
+                    error!("Event processor failed: {}", e);
+                    RaceEnd::Failed
+                }
+            },
+            failed = &mut self.streams => {
+                info!("All streams ended, shutting down");
+                RaceEnd::StreamsEnded { failed: failed.unwrap_or(true) }
+            }
+        };
+        self.ended = Some(end);
+        end
+    }
+
+    // Cancel the streams, process what they already reported, close every open race,
+    // then print and write the final summary and let the exporter finish
+    pub async fn shutdown(self) {
+        // Cancel the streams first, so the processor drains a queue that no longer grows
+        for task in &self.tasks {
+            task.abort();
+        }
+        let _ = self.shutdown_tx.send(true);
+        // The processor is already done once the race completed or failed
+        if !matches!(self.ended, Some(RaceEnd::Complete | RaceEnd::Failed)) {
+            let _ = self.processor.await;
+        }
+
+        self.referee.close_all().await;
+        self.referee.print_summary().await;
+        if let Some(writer) = &self.json_writer {
+            if let Err(e) = writer.write_summary(&self.referee, true).await {
+                error!("Failed to write JSON summary: {}", e);
+            }
+        }
+        // Let the exporter drain the closed races before returning
+        if let Some(export) = self.export {
+            let _ = export.await;
+        }
+    }
+}
@@ -1,17 +1,196 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::sync::Arc;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
 use tokio::sync::{mpsc, RwLock};
-use tracing::info;
+
+use crate::config::{Config, ScoringConfig, WatchdogConfig};
+use crate::metrics::Metrics;
+use tracing::{debug, info, warn};
+use yellowstone_grpc_proto::prelude::SlotStatus;
+
+// The kind of update a race is run on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RaceKind {
+    Slot,
+    Transaction,
+    Account,
+    Block,
+    BlockMeta,
+    SlotStatus,
+}
+
+impl RaceKind {
+    // Plural noun used in summary lines ("Total slots tracked")
+    pub fn plural(&self) -> &'static str {
+        match self {
+            RaceKind::Slot => "slots",
+            RaceKind::Transaction => "transactions",
+            RaceKind::Account => "account updates",
+            RaceKind::Block => "blocks",
+            RaceKind::BlockMeta => "block metas",
+            RaceKind::SlotStatus => "slot status updates",
+        }
+    }
+
+    // Whether every slot produces an update - only these races show whether a stream keeps up with the chain
+    pub fn tracks_every_slot(&self) -> bool {
+        matches!(self, RaceKind::Slot | RaceKind::SlotStatus)
+    }
+}
+
+impl fmt::Display for RaceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaceKind::Slot => write!(f, "slot"),
+            RaceKind::Transaction => write!(f, "transaction"),
+            RaceKind::Account => write!(f, "account"),
+            RaceKind::Block => write!(f, "block"),
+            RaceKind::BlockMeta => write!(f, "block_meta"),
+            RaceKind::SlotStatus => write!(f, "slot_status"),
+        }
+    }
+}
+
+// How races a stream missed, or reported too late, count towards its latency percentiles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ScoringMode {
+    // Only races the stream reported - a stream that drops its slowest updates looks faster
+    Reported,
+    // Missed and late races count as at least the scoring timeout
+    Timeout,
+    // Missed and late races count as infinitely far behind
+    Infinite,
+}
+
+// Identifies a single race - every stream reporting the same key competes in it
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RaceKey {
+    Slot(u64),
+    Transaction { signature: String, slot: u64 },
+    Account { pubkey: String, slot: u64, write_version: u64 },
+    Block { slot: u64, blockhash: String },
+    BlockMeta { slot: u64, blockhash: String },
+    SlotStatus { slot: u64, status: SlotStatus },
+}
+
+impl RaceKey {
+    pub fn kind(&self) -> RaceKind {
+        match self {
+            RaceKey::Slot(_) => RaceKind::Slot,
+            RaceKey::Transaction { .. } => RaceKind::Transaction,
+            RaceKey::Account { .. } => RaceKind::Account,
+            RaceKey::Block { .. } => RaceKind::Block,
+            RaceKey::BlockMeta { .. } => RaceKind::BlockMeta,
+            RaceKey::SlotStatus { .. } => RaceKind::SlotStatus,
+        }
+    }
+
+    // Slot the update belongs to - used for the warmup period
+    pub fn slot(&self) -> u64 {
+        match self {
+            RaceKey::Slot(slot) => *slot,
+            RaceKey::Transaction { slot, .. } => *slot,
+            RaceKey::Account { slot, .. } => *slot,
+            RaceKey::Block { slot, .. } => *slot,
+            RaceKey::BlockMeta { slot, .. } => *slot,
+            RaceKey::SlotStatus { slot, .. } => *slot,
+        }
+    }
+}
+
+impl fmt::Display for RaceKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RaceKey::Slot(slot) => write!(f, "Slot {}", slot),
+            RaceKey::Transaction { signature, slot } => write!(f, "Tx {} (slot {})", signature, slot),
+            RaceKey::Account { pubkey, slot, write_version } => {
+                write!(f, "Account {} (slot {}, write_version {})", pubkey, slot, write_version)
+            }
+            RaceKey::Block { slot, blockhash } => write!(f, "Block {} ({})", slot, blockhash),
+            RaceKey::BlockMeta { slot, blockhash } => write!(f, "BlockMeta {} ({})", slot, blockhash),
+            RaceKey::SlotStatus { slot, status } => write!(f, "Slot {} [{}]", slot, slot_status_label(*status)),
+        }
+    }
+}
+
+// Stages in the order a slot normally moves through them
+const SLOT_STAGES: [SlotStatus; 7] = [
+    SlotStatus::SlotFirstShredReceived,
+    SlotStatus::SlotCreatedBank,
+    SlotStatus::SlotCompleted,
+    SlotStatus::SlotProcessed,
+    SlotStatus::SlotConfirmed,
+    SlotStatus::SlotFinalized,
+    SlotStatus::SlotDead,
+];
+
+pub fn slot_status_label(status: SlotStatus) -> &'static str {
+    match status {
+        SlotStatus::SlotProcessed => "processed",
+        SlotStatus::SlotConfirmed => "confirmed",
+        SlotStatus::SlotFinalized => "finalized",
+        SlotStatus::SlotFirstShredReceived => "first_shred_received",
+        SlotStatus::SlotCompleted => "completed",
+        SlotStatus::SlotCreatedBank => "created_bank",
+        SlotStatus::SlotDead => "dead",
+    }
+}
 
 #[derive(Debug, Clone)]
-pub struct SlotResult {
-    pub slot: u64,
+pub struct RaceResult {
+    pub key: RaceKey,
     pub winner: String,
     pub winner_timestamp: u128,
     pub finish_times: HashMap<String, u128>, // All finish times including winner
+    pub created_at: HashMap<String, u128>, // Provider created_at on the same timeline, when sent
+    pub duplicates: HashMap<String, usize>, // Repeat deliveries of this key per stream
+    pub reorder_depths: HashMap<String, u64>, // How far below its previous max slot a stream delivered this slot
+    pub late: HashMap<String, u128>, // Reports that arrived after the race closed - not part of the race
+    pub replayed: HashMap<String, u128>, // History a stream replayed after resuming - delivered, but not raced
+    pub closed: Option<CloseReason>, // None while the race is still accepting reports
+}
+
+impl RaceResult {
+    // Streams that delivered this update, live or replayed
+    pub fn delivered_count(&self) -> usize {
+        self.finish_times.len() + self.replayed.len()
+    }
+}
+
+// Why a race stopped accepting reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CloseReason {
+    // Every stream reported
+    Complete,
+    // The race deadline passed before every stream reported
+    Deadline,
+    // The referee closed every open race on the way out
+    Shutdown,
+}
+
+impl fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CloseReason::Complete => write!(f, "complete"),
+            CloseReason::Deadline => write!(f, "deadline"),
+            CloseReason::Shutdown => write!(f, "shutdown"),
+        }
+    }
+}
+
+// Emitted once per race when it closes - consumed by the exporter and metrics
+#[derive(Debug, Clone)]
+pub struct RaceClosed {
+    pub reason: CloseReason,
+    pub result: RaceResult,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StreamMetrics {
     pub name: String,
     pub wins: usize,
@@ -21,47 +200,204 @@ pub struct StreamMetrics {
     pub p90_time_behind_ms: f64,  // 90th percentile (worst 10%)
     pub p95_time_behind_ms: f64,  // 95th percentile (worst 5%)
     pub p99_time_behind_ms: f64,  // 99th percentile (worst 1%)
+    // Receive time minus provider created_at - network and queueing on the way to us
+    pub median_transport_delay_ms: Option<f64>,
+    // Provider created_at behind the earliest created_at of any stream - provider-internal lag
+    pub median_provider_delay_ms: Option<f64>,
+    // Application-level ping round trips over the gRPC stream
+    pub min_rtt_ms: Option<f64>,
+    pub median_rtt_ms: Option<f64>,
+    pub p99_rtt_ms: Option<f64>,
+    // Races since the stream's first report that other streams delivered but this one never did
+    pub missed: usize,
+    pub missed_rate: f64,
+    // Reports that arrived after the race deadline had closed the race
+    pub late: usize,
+    // Races the stream only delivered as history after resuming - complete, but not scored
+    pub replayed: usize,
+    // Missed or late races scored as a penalty in the latency percentiles
+    pub penalized: usize,
+    // Repeat deliveries of a race the stream already reported
+    pub duplicates: usize,
+    // Slots delivered below the stream's previous max slot, and the furthest below it one arrived
+    pub reorders: usize,
+    pub max_reorder_depth: u64,
+}
+
+// Totals for one race kind (or one slot stage) with streams ranked fastest first
+#[derive(Debug, Clone, Serialize)]
+pub struct RaceSummary {
+    pub race: String,
+    #[serde(skip)]
+    pub noun: &'static str,
+    pub stream_count: usize,
+    pub total_tracked: usize,
+    pub completed_races: usize,
+    pub partial_results: usize,
+    // Races still inside their deadline, waiting for more streams
+    pub open_races: usize,
+    pub streams: Vec<StreamMetrics>,
 }
 
 // Event types for the channel
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum RaceEvent {
-    SlotReport { 
-        slot: u64, 
-        stream_id: String, 
-        timestamp: u128 
+    Report {
+        key: RaceKey,
+        stream_id: String,
+        timestamp: u128,
+        created_at: Option<u128>,
+    },
+    Connecting {
+        stream_id: String,
+        timestamp: u128,
+    },
+    Connected {
+        stream_id: String,
+        timestamp: u128,
+        // Slot the subscription asked the provider to replay from, when resuming
+        resumed_from: Option<u64>,
+    },
+    StreamError {
+        stream_id: String,
+        // Category the disconnect is counted under, e.g. `gRPC Unavailable` - not the full error text
+        error: String,
+        timestamp: u128,
+    },
+    Pong {
+        stream_id: String,
+        rtt_ns: u128,
+    },
+    // Periodic race clock time - closes races past their deadline after every report queued before it
+    Tick {
+        timestamp: u128,
     },
 }
 
+// Connection history of one stream, for uptime accounting
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectionSummary {
+    pub name: String,
+    pub attempts: usize,
+    pub connects: usize,
+    pub failed_attempts: usize,
+    pub disconnects: usize,
+    // Disconnect category -> how often it ended a connection or a connection attempt
+    pub disconnect_reasons: HashMap<String, usize>,
+    pub connected_ms: f64,
+    pub disconnected_ms: f64,
+    pub uptime_pct: f64,
+    // Times the watchdog forced a reconnect because the stream fell behind the leader
+    pub stalls: usize,
+}
+
+// Per-stream delivery tracking
+#[derive(Default)]
+struct StreamState {
+    // Lowest slot this stream reported - races before it are not counted as missed
+    first_slot: Option<u64>,
+    // Highest slot reported on the slot race, for gap detection
+    last_slot: Option<u64>,
+    // Highest slot reported on any race
+    highest_slot: Option<u64>,
+    // Highest slot reported on a race that tracks every slot - what the watchdog compares
+    progress_slot: Option<u64>,
+    // Race clock time of the latest report on a race that tracks every slot, live or replayed
+    last_report_at: Option<u128>,
+    // When the watchdog first saw this stream behind the leader
+    behind_since: Option<u128>,
+    // After resuming, updates up to this slot are replayed history
+    replay_until: Option<u64>,
+}
+
+// Ping round trips kept per stream for the RTT percentiles
+const MAX_RTT_SAMPLES: usize = 1000;
+
+// Per-stream connection tracking
+#[derive(Default)]
+struct ConnectionState {
+    attempts: usize,
+    connects: usize,
+    disconnects: usize,
+    reasons: HashMap<String, usize>,
+    // Race clock time of the first connection attempt - uptime is measured from here
+    first_attempt_at: Option<u128>,
+    connected_since: Option<u128>,
+    // Total time spent connected, not counting the current connection
+    connected_ns: u128,
+    stalls: usize,
+    // Most recent ping round trips in milliseconds
+    rtts_ms: VecDeque<f64>,
+}
+
 // Inner state that needs to be mutable
 struct RefereeState {
-    results: VecDeque<SlotResult>,
+    // Rolling window of results for each enabled race kind
+    results: HashMap<RaceKind, VecDeque<RaceResult>>,
+    // Keys of races that left the window, oldest first with the time they left - reports for them are late
+    evicted: VecDeque<(u128, RaceKey)>,
+    evicted_keys: HashSet<RaceKey>,
     stream_names: Vec<String>,
     first_slot: Option<u64>,
+    // Consumers of RaceClosed events
+    closed_subscribers: Vec<mpsc::UnboundedSender<RaceClosed>>,
+    // Consumers of every processed RaceEvent
+    event_subscribers: Vec<mpsc::UnboundedSender<RaceEvent>>,
+    connections: HashMap<String, ConnectionState>,
+    // Latest race clock time seen - advanced by events and the deadline ticker
+    now: u128,
+    streams: HashMap<String, StreamState>,
 }
 
 pub struct Referee {
     max_slots: usize,
+    // Configured streams - a race is only complete once every one of them has reported
+    configured_streams: Vec<String>,
     stop_at_max: bool,
     warmup_slots: usize,
+    races: Vec<RaceKind>,
+    // How long after its first report a race keeps accepting reports
+    race_deadline_ns: u128,
+    scoring: ScoringConfig,
+    watchdog: WatchdogConfig,
+    ping_interval_ms: u64,
+    metrics: Option<Arc<Metrics>>,
     state: Arc<RwLock<RefereeState>>,
     event_tx: mpsc::UnboundedSender<RaceEvent>,
 }
 
 impl Referee {
-    pub fn new(max_slots: usize, stop_at_max: bool, warmup_slots: usize) -> (Arc<Self>, mpsc::UnboundedReceiver<RaceEvent>) {
+    pub fn new(config: &Config, metrics: Option<Arc<Metrics>>) -> (Arc<Self>, mpsc::UnboundedReceiver<RaceEvent>) {
         let (tx, rx) = mpsc::unbounded_channel();
 
+        let results = config.races.iter()
+            .map(|kind| (*kind, VecDeque::with_capacity(config.max_slots)))
+            .collect();
+
         let state = Arc::new(RwLock::new(RefereeState {
-            results: VecDeque::with_capacity(max_slots),
+            results,
+            evicted: VecDeque::new(),
+            evicted_keys: HashSet::new(),
             stream_names: Vec::new(),
             first_slot: None,
+            closed_subscribers: Vec::new(),
+            event_subscribers: Vec::new(),
+            connections: HashMap::new(),
+            now: 0,
+            streams: HashMap::new(),
         }));
 
         let referee = Arc::new(Self {
-            max_slots,
-            stop_at_max,
-            warmup_slots,
+            max_slots: config.max_slots,
+            configured_streams: config.streams.iter().map(|stream| stream.name.clone()).collect(),
+            stop_at_max: config.stop_at_max,
+            warmup_slots: config.warmup_slots,
+            races: config.races.clone(),
+            race_deadline_ns: config.race_deadline_ms as u128 * 1_000_000,
+            scoring: config.scoring.clone(),
+            watchdog: config.watchdog.clone(),
+            ping_interval_ms: config.ping_interval_ms,
+            metrics,
             state,
             event_tx: tx,
         });
@@ -69,30 +405,263 @@ impl Referee {
         (referee, rx)
     }
     
+    // The race is complete once every enabled race kind has filled its window
     pub async fn is_complete(&self) -> bool {
         let state = self.state.read().await;
-        self.stop_at_max && state.results.len() >= self.max_slots
+        self.stop_at_max && state.results.values().all(|results| results.len() >= self.max_slots)
     }
     
+    // Receive a RaceClosed event for every race as it closes
+    pub async fn subscribe(&self) -> mpsc::UnboundedReceiver<RaceClosed> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.state.write().await.closed_subscribers.push(tx);
+        rx
+    }
+
+    // Receive every race event once it has been processed
+    pub async fn subscribe_events(&self) -> mpsc::UnboundedReceiver<RaceEvent> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.state.write().await.event_subscribers.push(tx);
+        rx
+    }
+
+    // Apply one race event, returning true once the race is complete - called by the event processor
+    pub async fn process_event(&self, event: RaceEvent) -> bool {
+        let published = (!self.state.read().await.event_subscribers.is_empty()).then(|| event.clone());
+
+        let complete = match event {
+            RaceEvent::Report { key, stream_id, timestamp, created_at } => {
+                let should_continue = self.process_report(key, stream_id, timestamp, created_at).await;
+                !should_continue && self.is_complete().await
+            }
+            RaceEvent::Connecting { stream_id, timestamp } => {
+                self.process_connecting(stream_id, timestamp).await;
+                false
+            }
+            RaceEvent::Connected { stream_id, timestamp, resumed_from } => {
+                self.process_connected(stream_id, timestamp, resumed_from).await;
+                false
+            }
+            RaceEvent::StreamError { stream_id, error, timestamp } => {
+                self.process_stream_error(stream_id, error, timestamp).await;
+                false
+            }
+            RaceEvent::Pong { stream_id, rtt_ns } => {
+                self.process_pong(stream_id, rtt_ns).await;
+                false
+            }
+            RaceEvent::Tick { timestamp } => {
+                self.close_expired(timestamp).await;
+                false
+            }
+        };
+
+        if let Some(event) = published {
+            self.state.write().await.event_subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+        }
+        complete
+    }
+
     // Non-blocking send method for streams to report slots
-    pub fn send_slot(&self, slot: u64, stream_id: String, timestamp: u128) {
-        let _ = self.event_tx.send(RaceEvent::SlotReport { slot, stream_id, timestamp });
+    pub fn send_slot(&self, slot: u64, stream_id: String, timestamp: u128, created_at: Option<u128>) {
+        self.send_report(RaceKey::Slot(slot), stream_id, timestamp, created_at);
+    }
+
+    // Non-blocking send method for streams to report any race key
+    pub fn send_report(&self, key: RaceKey, stream_id: String, timestamp: u128, created_at: Option<u128>) {
+        let _ = self.event_tx.send(RaceEvent::Report { key, stream_id, timestamp, created_at });
+    }
+
+    pub fn send_connecting(&self, stream_id: String, timestamp: u128) {
+        let _ = self.event_tx.send(RaceEvent::Connecting { stream_id, timestamp });
+    }
+
+    pub fn send_connected(&self, stream_id: String, timestamp: u128, resumed_from: Option<u64>) {
+        let _ = self.event_tx.send(RaceEvent::Connected { stream_id, timestamp, resumed_from });
+    }
+
+    pub fn send_stream_error(&self, stream_id: String, error: String, timestamp: u128) {
+        let _ = self.event_tx.send(RaceEvent::StreamError { stream_id, error, timestamp });
     }
 
-    // Process a slot report - called by the event processor
-    pub async fn process_slot_report(&self, slot: u64, stream_id: String, timestamp: u128) -> bool {
+    pub fn send_pong(&self, stream_id: String, rtt_ns: u128) {
+        let _ = self.event_tx.send(RaceEvent::Pong { stream_id, rtt_ns });
+    }
+
+    // Queued behind pending reports, so a backlog never closes a race before its on-time reports are counted
+    pub fn send_tick(&self, timestamp: u128) {
+        let _ = self.event_tx.send(RaceEvent::Tick { timestamp });
+    }
+
+    // Process a ping round trip - called by the event processor
+    pub async fn process_pong(&self, stream_id: String, rtt_ns: u128) {
+        let mut state = self.state.write().await;
+        let rtt_ms = rtt_ns as f64 / 1_000_000.0;
+        debug!("[{}] Ping round trip: {:.3}ms", stream_id, rtt_ms);
+
+        let rtts_ms = &mut state.connections.entry(stream_id).or_default().rtts_ms;
+        rtts_ms.push_back(rtt_ms);
+        if rtts_ms.len() > MAX_RTT_SAMPLES {
+            rtts_ms.pop_front();
+        }
+    }
+
+    // Process a connection attempt - called by the event processor
+    pub async fn process_connecting(&self, stream_id: String, timestamp: u128) {
         let mut state = self.state.write().await;
+        state.now = state.now.max(timestamp);
+
+        let connection = state.connections.entry(stream_id).or_default();
+        connection.attempts += 1;
+        connection.first_attempt_at.get_or_insert(timestamp);
+    }
+
+    // Process a connection event - called by the event processor
+    pub async fn process_connected(&self, stream_id: String, timestamp: u128, resumed_from: Option<u64>) {
+        let mut state = self.state.write().await;
+        state.now = state.now.max(timestamp);
+
+        // Whatever the leading stream already delivered is history for a resumed stream
+        if let Some(from_slot) = resumed_from {
+            let leader_slot = state.streams.values().filter_map(|s| s.highest_slot).max();
+            if let Some(until) = leader_slot.filter(|until| *until >= from_slot) {
+                info!("[{}] Resumed from slot {} - updates up to slot {} are replays", stream_id, from_slot, until);
+                state.streams.entry(stream_id.clone()).or_default().replay_until = Some(until);
+            }
+        }
+
+        let connection = state.connections.entry(stream_id.clone()).or_default();
+        connection.connects += 1;
+        connection.first_attempt_at.get_or_insert(timestamp);
+        connection.connected_since = Some(timestamp);
+
+        if connection.connects > 1 {
+            info!("[{}] Reconnected (connection #{})", stream_id, connection.connects);
+            if let Some(metrics) = &self.metrics {
+                metrics.record_reconnect(&stream_id);
+            }
+        }
+        if let Some(metrics) = &self.metrics {
+            metrics.record_connection_up(&stream_id, true);
+        }
+    }
+
+    // Highest slot any stream has reported
+    pub async fn leader_slot(&self) -> Option<u64> {
+        self.state.read().await.streams.values().filter_map(|s| s.highest_slot).max()
+    }
+
+    // Streams a race waits for - every configured stream, plus any other that reported
+    fn stream_count(&self, stream_names: &[String]) -> usize {
+        self.race_streams(stream_names).len()
+    }
+
+    // Configured streams in config order, whether or not they reported, then any other that reported
+    fn race_streams(&self, stream_names: &[String]) -> Vec<String> {
+        let mut streams = self.configured_streams.clone();
+        streams.extend(stream_names.iter().filter(|name| !self.configured_streams.contains(name)).cloned());
+        streams
+    }
+
+    // How often subscriptions ping their provider, if at all
+    pub fn ping_interval(&self) -> Option<Duration> {
+        (self.ping_interval_ms > 0).then(|| Duration::from_millis(self.ping_interval_ms))
+    }
+
+    // How often subscriptions should ask check_stale about their stream
+    pub fn watchdog_interval(&self) -> Duration {
+        Duration::from_millis((self.watchdog.max_silence_ms / 4).clamp(50, 1000))
+    }
+
+    // Whether a stream has fallen too far behind the leader and should reconnect - counts the stall if so.
+    // The reason is a fixed category, so disconnect reasons group by it - the details are only logged.
+    pub async fn check_stale(&self, stream_id: &str, now: u128) -> Option<&'static str> {
+        if !self.watchdog.enabled {
+            return None;
+        }
+
+        let mut guard = self.state.write().await;
+        let state = &mut *guard;
+
+        // Sparse races like account or transaction updates can't tell a quiet stream from a stuck one
+        let leader_slot = state.streams.values().filter_map(|s| s.progress_slot).max()?;
+        let connection = state.connections.get_mut(stream_id)?;
+        let stream_state = state.streams.entry(stream_id.to_string()).or_default();
+        let stream_slot = stream_state.progress_slot;
+        if stream_slot.is_some_and(|slot| slot >= leader_slot) {
+            stream_state.behind_since = None;
+            return None;
+        }
+        let behind_since = *stream_state.behind_since.get_or_insert(now);
+
+        // Silence counts from whatever happened last: falling behind, the latest report or the connection
+        let active_since = Some(behind_since)
+            .max(stream_state.last_report_at)
+            .max(connection.connected_since)
+            .unwrap_or(now);
+        let silent_ms = now.saturating_sub(active_since) / 1_000_000;
+
+        // A replaying stream is catching up on purpose, so only its silence counts
+        let replaying = stream_state.replay_until.is_some();
+        let slots_behind = stream_slot.map(|slot| leader_slot - slot).filter(|_| !replaying);
+
+        let (reason, details) = if slots_behind.is_some_and(|behind| behind > self.watchdog.max_slots_behind) {
+            ("too far behind the leader", format!("{} slots behind", slots_behind.unwrap()))
+        } else if silent_ms > self.watchdog.max_silence_ms as u128 {
+            ("silent while behind the leader", format!("no updates for {}ms while the leader is at slot {}", silent_ms, leader_slot))
+        } else {
+            return None;
+        };
+
+        stream_state.behind_since = None;
+        connection.stalls += 1;
+        warn!("[{}] Stream stalled: {} ({}) - forcing a reconnect", stream_id, reason, details);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_stall(stream_id);
+        }
+        Some(reason)
+    }
+
+    // Process a stream error - ends the current connection, or a failed attempt - called by the event processor
+    pub async fn process_stream_error(&self, stream_id: String, error: String, timestamp: u128) {
+        debug!("[{}] Disconnect recorded: {}", stream_id, error);
+        let mut state = self.state.write().await;
+        state.now = state.now.max(timestamp);
+
+        let connection = state.connections.entry(stream_id.clone()).or_default();
+        if let Some(connected_since) = connection.connected_since.take() {
+            connection.connected_ns += timestamp.saturating_sub(connected_since);
+            connection.disconnects += 1;
+        }
+        *connection.reasons.entry(error).or_default() += 1;
+
+        if let Some(metrics) = &self.metrics {
+            metrics.record_stream_error(&stream_id);
+            metrics.record_connection_up(&stream_id, false);
+        }
+    }
+
+    // Process a race report - called by the event processor
+    pub async fn process_report(&self, key: RaceKey, stream_id: String, timestamp: u128, created_at: Option<u128>) -> bool {
+        let mut guard = self.state.write().await;
+        let state = &mut *guard;
+        let slot = key.slot();
+
+        // Reports are stamped on the race clock, so they double as the current time
+        state.now = state.now.max(timestamp);
+        self.close_expired_results(state, timestamp);
+        self.forget_evicted(state, timestamp);
 
         // Track first slot seen for warmup calculation
         if state.first_slot.is_none() {
             state.first_slot = Some(slot);
             info!("First slot seen: {}. Warmup period: {} slots (race starts at slot {})",
-                slot, self.warmup_slots, slot + self.warmup_slots as u64);
+                slot, self.warmup_slots, slot.saturating_add(self.warmup_slots as u64));
         }
 
         // Check if we're still in warmup period
         let first_slot = state.first_slot.unwrap();
-        let race_start_slot = first_slot + self.warmup_slots as u64;
+        let race_start_slot = first_slot.saturating_add(self.warmup_slots as u64);
         let is_warmup = slot < race_start_slot;
 
         // Track unique stream names (even during warmup)
@@ -100,11 +669,122 @@ impl Referee {
             state.stream_names.push(stream_id.clone());
         }
 
+        if let Some(metrics) = &self.metrics {
+            metrics.record_report(&key.kind().to_string(), &stream_id, slot);
+        }
+
+        // Updates a resumed stream replays from before it reconnected are history, not races
+        let stream_state = state.streams.entry(stream_id.clone()).or_default();
+        if key.kind().tracks_every_slot() {
+            stream_state.last_report_at = Some(timestamp);
+        }
+        let is_replay = match stream_state.replay_until {
+            Some(until) if slot <= until => true,
+            Some(_) => {
+                info!("[{}] Caught up after resuming", stream_id);
+                stream_state.replay_until = None;
+                false
+            }
+            None => false,
+        };
+
+        // A repeat delivery must not overwrite the stream's first finish time
+        if let Some(existing) = state.results.get_mut(&key.kind()).and_then(|results| results.iter_mut().rev().find(|r| r.key == key)) {
+            if existing.finish_times.contains_key(&stream_id) || existing.replayed.contains_key(&stream_id) {
+                // Resuming re-sends the slot the stream disconnected on - expected, not a duplicate
+                if is_replay {
+                    return true;
+                }
+                *existing.duplicates.entry(stream_id.clone()).or_default() += 1;
+                warn!("[{}] Duplicate delivery of {}", stream_id, key);
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_duplicate(&key.kind().to_string(), &stream_id);
+                }
+                return true;
+            }
+
+            // Replays count towards completeness, but never towards the race itself
+            if is_replay {
+                existing.replayed.insert(stream_id.clone(), timestamp);
+                debug!("{} - {} replayed after resuming (not raced)", key, stream_id);
+                if existing.delivered_count() == self.stream_count(&state.stream_names) {
+                    self.close_result(&state.closed_subscribers, existing, CloseReason::Complete);
+                }
+                return true;
+            }
+
+            // The deadline already closed this race - note the report, but keep it out of the race
+            if existing.closed.is_some() {
+                if !existing.late.contains_key(&stream_id) {
+                    existing.late.insert(stream_id.clone(), timestamp);
+                    warn!(
+                        "{} - {} reported late (+{:.3}ms, after the race closed)",
+                        key,
+                        stream_id,
+                        timestamp.saturating_sub(existing.winner_timestamp) as f64 / 1_000_000.0
+                    );
+                    if let Some(metrics) = &self.metrics {
+                        metrics.record_late(&key.kind().to_string(), &stream_id);
+                    }
+                }
+                return true;
+            }
+        } else if is_replay {
+            debug!("{} - {} replayed an update outside the race window, ignoring", key, stream_id);
+            return true;
+        } else if state.evicted_keys.contains(&key) {
+            // The race already closed and left the window - starting it again would hand this stream a win
+            warn!("{} - {} reported late (after the race left the window)", key, stream_id);
+            if let Some(metrics) = &self.metrics {
+                metrics.record_late(&key.kind().to_string(), &stream_id);
+            }
+            return true;
+        }
+
+        let stream_state = state.streams.entry(stream_id.clone()).or_default();
+        stream_state.first_slot = Some(stream_state.first_slot.map_or(slot, |first| first.min(slot)));
+        stream_state.highest_slot = Some(stream_state.highest_slot.map_or(slot, |highest| highest.max(slot)));
+        if key.kind().tracks_every_slot() {
+            stream_state.progress_slot = Some(stream_state.progress_slot.map_or(slot, |progress| progress.max(slot)));
+        }
+
+        // Detect gaps and out-of-order delivery in this stream's slot sequence
+        let mut reorder_depth = None;
+        if key.kind() == RaceKind::Slot {
+            if let Some(last_slot) = stream_state.last_slot.filter(|last| slot < *last) {
+                let depth = last_slot - slot;
+                warn!("[{}] Out-of-order delivery: slot {} arrived after slot {} ({} behind)", stream_id, slot, last_slot, depth);
+                if let Some(metrics) = &self.metrics {
+                    metrics.record_reorder(&stream_id);
+                }
+                reorder_depth = Some(depth);
+            }
+
+            if let (Some(last_slot), Some(slot_results)) = (stream_state.last_slot, state.results.get(&RaceKind::Slot)) {
+                if slot > last_slot + 1 {
+                    let skipped = slot_results.iter()
+                        .filter(|r| r.key.slot() > last_slot && r.key.slot() < slot)
+                        .filter(|r| !r.finish_times.contains_key(&stream_id) && !r.replayed.contains_key(&stream_id))
+                        .count();
+                    if skipped > 0 {
+                        warn!(
+                            "[{}] Gap detected: jumped from slot {} to {}, missing {} slots other streams delivered",
+                            stream_id,
+                            last_slot,
+                            slot,
+                            skipped
+                        );
+                    }
+                }
+            }
+            stream_state.last_slot = Some(stream_state.last_slot.map_or(slot, |last| last.max(slot)));
+        }
+
         // During warmup, just log and skip counting
         if is_warmup {
             info!(
-                "[WARMUP] Slot {} - {} ({}ns) - {} slots until race starts",
-                slot,
+                "[WARMUP] {} - {} ({}ns) - {} slots until race starts",
+                key,
                 stream_id,
                 timestamp,
                 race_start_slot - slot
@@ -112,21 +792,32 @@ impl Referee {
             return true;
         }
 
+        // Ignore race kinds that were not enabled
+        let Some(results) = state.results.get_mut(&key.kind()) else {
+            return true;
+        };
+
         // If we're at max capacity and should stop, return false to signal completion
-        if self.stop_at_max && state.results.len() >= self.max_slots {
-            // Check if this is a new slot (not already in results)
-            if !state.results.iter().any(|r| r.slot == slot) {
+        if self.stop_at_max && results.len() >= self.max_slots {
+            // Check if this is a new key (not already in results)
+            if !results.iter().any(|r| r.key == key) {
                 return false;
             }
         }
 
         // Get the number of streams before we start borrowing results
-        let num_streams = state.stream_names.len();
+        let num_streams = self.stream_count(&state.stream_names);
 
-        // Check if this slot already exists
-        if let Some(existing) = state.results.iter_mut().find(|r| r.slot == slot) {
+        // Check if this key already exists - recent keys live at the back
+        if let Some(existing) = results.iter_mut().rev().find(|r| r.key == key) {
             // Add this stream's finish time
             existing.finish_times.insert(stream_id.clone(), timestamp);
+            if let Some(created_at) = created_at {
+                existing.created_at.insert(stream_id.clone(), created_at);
+            }
+            if let Some(depth) = reorder_depth {
+                existing.reorder_depths.insert(stream_id.clone(), depth);
+            }
 
             // Check if this stream actually had a faster timestamp than current winner
             if timestamp < existing.winner_timestamp {
@@ -134,8 +825,8 @@ impl Referee {
                 existing.winner = stream_id.clone();
                 existing.winner_timestamp = timestamp;
                 info!(
-                    "Slot {} - {} arrived later but had faster timestamp! New winner (was {})",
-                    slot,
+                    "{} - {} arrived later but had faster timestamp! New winner (was {})",
+                    key,
                     stream_id,
                     old_winner
                 );
@@ -152,8 +843,8 @@ impl Referee {
 
             // Unified logging format
             info!(
-                "Slot {} - Position {}/{}: {} ({}ns, +{:.3}ms)",
-                slot,
+                "{} - Position {}/{}: {} ({}ns, +{:.3}ms)",
+                key,
                 position,
                 num_streams,
                 stream_id,
@@ -162,55 +853,183 @@ impl Referee {
             );
 
             // If all streams have reported, log race completion
-            if existing.finish_times.len() == num_streams {
+            if existing.delivered_count() == num_streams {
                 let slowest_time = existing.finish_times.values().max().unwrap_or(&timestamp);
                 let spread_ms = (*slowest_time - existing.winner_timestamp) as f64 / 1_000_000.0;
                 info!(
-                    "Slot {} race complete! All {} streams reported. Winner: {} ({:.3}ms ahead of last)",
-                    slot,
+                    "{} race complete! All {} streams reported. Winner: {} ({:.3}ms ahead of last)",
+                    key,
                     num_streams,
                     existing.winner,
                     spread_ms
                 );
+
+                self.close_result(&state.closed_subscribers, existing, CloseReason::Complete);
             }
         } else {
-            // This is the first report for this slot (current winner)
+            // This is the first report for this key (current winner)
             let mut finish_times = HashMap::new();
             finish_times.insert(stream_id.clone(), timestamp);
 
-            let result = SlotResult {
-                slot,
+            let mut created_at_times = HashMap::new();
+            if let Some(created_at) = created_at {
+                created_at_times.insert(stream_id.clone(), created_at);
+            }
+
+            let mut reorder_depths = HashMap::new();
+            if let Some(depth) = reorder_depth {
+                reorder_depths.insert(stream_id.clone(), depth);
+            }
+
+            let result = RaceResult {
+                key: key.clone(),
                 winner: stream_id.clone(),
                 winner_timestamp: timestamp,
                 finish_times,
+                created_at: created_at_times,
+                duplicates: HashMap::new(),
+                reorder_depths,
+                late: HashMap::new(),
+                replayed: HashMap::new(),
+                closed: None,
             };
 
             // Unified logging format for first reporter
             info!(
-                "Slot {} - Position 1/{}: {} ({}ns, FIRST)",
-                slot,
+                "{} - Position 1/{}: {} ({}ns, FIRST)",
+                key,
                 num_streams,
                 stream_id,
                 timestamp
             );
 
             // Add to results
-            state.results.push_back(result);
+            results.push_back(result);
 
-            // Remove oldest if we exceed max_slots (only if not stopping at max)
-            if !self.stop_at_max && state.results.len() > self.max_slots {
-                state.results.pop_front();
+            // Remove the oldest beyond max_slots (only if not stopping at max) once they have closed -
+            // a busy transaction or account race fills the window long before its races' deadlines
+            if !self.stop_at_max {
+                while results.len() > self.max_slots && results.front().is_some_and(|r| r.closed.is_some()) {
+                    if let Some(oldest) = results.pop_front() {
+                        state.evicted_keys.insert(oldest.key.clone());
+                        state.evicted.push_back((timestamp, oldest.key));
+                    }
+                }
             }
         }
 
         true // Continue processing
     }
 
+    // Close a result once and tell subscribers - on completion, at its deadline or when it leaves the window
+    fn close_result(&self, subscribers: &[mpsc::UnboundedSender<RaceClosed>], result: &mut RaceResult, reason: CloseReason) {
+        if result.closed.is_some() {
+            return;
+        }
+        result.closed = Some(reason);
+
+        for subscriber in subscribers {
+            let _ = subscriber.send(RaceClosed { reason, result: result.clone() });
+        }
+    }
+
+    fn close_expired_results(&self, state: &mut RefereeState, now: u128) {
+        let num_streams = self.stream_count(&state.stream_names);
+        for results in state.results.values_mut() {
+            for result in results.iter_mut().filter(|r| r.closed.is_none()) {
+                if now.saturating_sub(result.winner_timestamp) < self.race_deadline_ns {
+                    continue;
+                }
+                debug!(
+                    "{} race closed at its deadline with {}/{} streams reported",
+                    result.key,
+                    result.finish_times.len(),
+                    num_streams
+                );
+                self.close_result(&state.closed_subscribers, result, CloseReason::Deadline);
+            }
+        }
+    }
+
+    // Evicted keys are remembered for another race deadline, so stragglers are counted late rather than raced
+    fn forget_evicted(&self, state: &mut RefereeState, now: u128) {
+        while let Some((evicted_at, _)) = state.evicted.front() {
+            if now.saturating_sub(*evicted_at) < self.race_deadline_ns {
+                break;
+            }
+            if let Some((_, key)) = state.evicted.pop_front() {
+                state.evicted_keys.remove(&key);
+            }
+        }
+    }
+
+    // Close races whose deadline has passed - called on every tick so races close even when no reports arrive
+    pub async fn close_expired(&self, now: u128) {
+        let mut state = self.state.write().await;
+        state.now = state.now.max(now);
+        self.close_expired_results(&mut state, now);
+    }
+
+    // Close every result still open (including partial races) and end the RaceClosed and RaceEvent streams
+    pub async fn close_all(&self) {
+        let mut guard = self.state.write().await;
+        let state = &mut *guard;
+        for results in state.results.values_mut() {
+            for result in results.iter_mut() {
+                self.close_result(&state.closed_subscribers, result, CloseReason::Shutdown);
+            }
+        }
+        state.closed_subscribers.clear();
+        state.event_subscribers.clear();
+    }
+
+    // Race summaries for every enabled race kind, for machine-readable output
+    pub async fn summarize(&self) -> Vec<RaceSummary> {
+        let state = self.state.read().await;
+        self.build_summaries(&state).await
+    }
+
+    // Connection history of every stream, sorted by name
+    pub async fn connection_summaries(&self) -> Vec<ConnectionSummary> {
+        let state = self.state.read().await;
+        self.build_connection_summaries(&state)
+    }
+
+    fn build_connection_summaries(&self, state: &RefereeState) -> Vec<ConnectionSummary> {
+        let mut summaries: Vec<ConnectionSummary> = state.connections.iter()
+            .map(|(name, connection)| {
+                let connected_ns = connection.connected_ns
+                    + connection.connected_since.map_or(0, |since| state.now.saturating_sub(since));
+                let tracked_ns = connection.first_attempt_at.map_or(0, |first| state.now.saturating_sub(first));
+                let disconnected_ns = tracked_ns.saturating_sub(connected_ns);
+
+                ConnectionSummary {
+                    name: name.clone(),
+                    attempts: connection.attempts,
+                    connects: connection.connects,
+                    failed_attempts: connection.attempts.saturating_sub(connection.connects),
+                    disconnects: connection.disconnects,
+                    disconnect_reasons: connection.reasons.clone(),
+                    connected_ms: connected_ns as f64 / 1_000_000.0,
+                    disconnected_ms: disconnected_ns as f64 / 1_000_000.0,
+                    uptime_pct: if tracked_ns == 0 {
+                        0.0
+                    } else {
+                        (connected_ns.min(tracked_ns) as f64 / tracked_ns as f64) * 100.0
+                    },
+                    stalls: connection.stalls,
+                }
+            })
+            .collect();
+
+        summaries.sort_by(|a, b| a.name.cmp(&b.name));
+        summaries
+    }
+    
     pub async fn print_summary(&self) {
         let state = self.state.read().await;
         
         info!("=== RACE SUMMARY ===");
-        info!("Total slots tracked: {}", state.results.len());
         
         if state.stream_names.is_empty() {
             info!("No streams have reported yet");
@@ -218,94 +1037,305 @@ impl Referee {
             return;
         }
         
-        // Calculate comprehensive metrics for all streams
-        let metrics = self.calculate_stream_metrics(&state).await;
+        let summaries = self.build_summaries(&state).await;
+        for summary in &summaries {
+            self.print_race_summary(summary);
+        }
         
-        // Count completed races
-        let completed_races = state.results.iter()
-            .filter(|r| r.finish_times.len() == state.stream_names.len())
-            .count();
+        // Show each stream's latency for every race kind side by side
+        if summaries.len() > 1 {
+            info!("");
+            info!("Median time behind by race kind:");
+            for stream_name in &self.race_streams(&state.stream_names) {
+                let latencies: Vec<String> = summaries.iter()
+                    .map(|summary| {
+                        match summary.streams.iter().find(|m| m.name == *stream_name) {
+                            Some(metric) => format!("{}: {:.3}ms", summary.race, metric.median_time_behind_ms),
+                            None => format!("{}: n/a", summary.race),
+                        }
+                    })
+                    .collect();
+                info!("   {} - {}", stream_name, latencies.join(", "));
+            }
+        }
         
-        info!("Completed races (all {} streams reported): {}", state.stream_names.len(), completed_races);
-        info!("Partial results included: {}", state.results.len() - completed_races);
+        self.print_connection_summaries(&self.build_connection_summaries(&state));
         
-        // Sort streams by median time behind (ascending - fastest first)
-        let mut sorted_metrics = metrics;
-        sorted_metrics.sort_by(|a, b| a.median_time_behind_ms.partial_cmp(&b.median_time_behind_ms).unwrap());
+        info!("==================");
+    }
+    
+    fn print_connection_summaries(&self, summaries: &[ConnectionSummary]) {
+        if summaries.is_empty() {
+            return;
+        }
+        
+        info!("");
+        info!("Connections:");
+        for summary in summaries {
+            info!("   {} - Uptime: {:.2}% (disconnected {:.1}s), connects: {}/{} attempts, disconnects: {}, stalls: {}",
+                summary.name,
+                summary.uptime_pct,
+                summary.disconnected_ms / 1000.0,
+                summary.connects,
+                summary.attempts,
+                summary.disconnects,
+                summary.stalls);
+            
+            // Most frequent reasons first
+            let mut reasons: Vec<_> = summary.disconnect_reasons.iter().collect();
+            reasons.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+            for (reason, count) in reasons.iter().take(3) {
+                info!("      {}x {}", count, reason);
+            }
+        }
+    }
+    
+    fn print_race_summary(&self, summary: &RaceSummary) {
+        info!("--- {} races ---", summary.race);
+        info!("Total {} tracked: {}", summary.noun, summary.total_tracked);
+        info!("Completed races (all {} streams reported): {}", summary.stream_count, summary.completed_races);
+        info!("Partial results included: {} ({} still open)", summary.partial_results, summary.open_races);
         
         info!("");
         info!("Stream Performance Metrics:");
         info!("");
         
-        for (rank, metric) in sorted_metrics.iter().enumerate() {
+        for (rank, metric) in summary.streams.iter().enumerate() {
             info!("{}. {} - Wins: {}/{} ({:.1}%)", 
                 rank + 1, metric.name, metric.wins, metric.total_races, metric.win_rate);
-            info!("   Median time behind: {:.3}ms", metric.median_time_behind_ms);
+            match (metric.min_rtt_ms, metric.median_rtt_ms, metric.p99_rtt_ms) {
+                (Some(min), Some(median), Some(p99)) => info!(
+                    "   Median time behind: {:.3}ms (gRPC RTT min: {:.3}ms, median: {:.3}ms, P99: {:.3}ms)",
+                    metric.median_time_behind_ms, min, median, p99
+                ),
+                _ => info!("   Median time behind: {:.3}ms", metric.median_time_behind_ms),
+            }
             info!("   Worst-case latencies: P90: {:.3}ms, P95: {:.3}ms, P99: {:.3}ms",
                 metric.p90_time_behind_ms, metric.p95_time_behind_ms, metric.p99_time_behind_ms);
+            info!("   Missed: {} ({:.1}% of races since first report), late: {}, replayed: {}, penalized: {}",
+                metric.missed, metric.missed_rate, metric.late, metric.replayed, metric.penalized);
+            info!("   Duplicates: {}, out-of-order: {} (max depth {} slots)",
+                metric.duplicates, metric.reorders, metric.max_reorder_depth);
+            if let (Some(transport), Some(provider)) = (metric.median_transport_delay_ms, metric.median_provider_delay_ms) {
+                info!("   Median transport delay (receive - created_at): {:.3}ms, provider delay (behind earliest created_at): {:.3}ms",
+                    transport, provider);
+            }
             info!("");
         }
         
-        // Overall winner
-        if let Some(leader) = sorted_metrics.first() {
-            info!(">>> {} is the fastest overall", leader.name);
+        // Overall winner for this race kind
+        if let Some(leader) = summary.streams.first() {
+            info!(">>> {} is the fastest overall for {} races", leader.name, summary.race);
         }
+    }
+    
+    async fn build_summaries(&self, state: &RefereeState) -> Vec<RaceSummary> {
+        let mut summaries = Vec::new();
         
-        info!("==================");
+        for kind in &self.races {
+            let Some(results) = state.results.get(kind) else {
+                continue;
+            };
+            
+            if *kind == RaceKind::SlotStatus {
+                // Each stage gets its own breakdown so slow confirmations stand out
+                for stage in SLOT_STAGES {
+                    let stage_results: Vec<&RaceResult> = results.iter()
+                        .filter(|r| matches!(r.key, RaceKey::SlotStatus { status, .. } if status == stage))
+                        .collect();
+                    if stage_results.is_empty() {
+                        continue;
+                    }
+                    let label = format!("{}/{}", kind, slot_status_label(stage));
+                    summaries.push(self.summarize_race(label, kind.plural(), &stage_results, state).await);
+                }
+            } else {
+                let results: Vec<&RaceResult> = results.iter().collect();
+                summaries.push(self.summarize_race(kind.to_string(), kind.plural(), &results, state).await);
+            }
+        }
+        
+        summaries
+    }
+    
+    async fn summarize_race(&self, race: String, noun: &'static str, results: &[&RaceResult], state: &RefereeState) -> RaceSummary {
+        let stream_count = self.stream_count(&state.stream_names);
+        
+        // Calculate comprehensive metrics for all streams
+        let metrics = self.calculate_stream_metrics(results, state).await;
+        
+        // Count completed races
+        let completed_races = results.iter()
+            .filter(|r| r.delivered_count() == stream_count)
+            .count();
+        
+        // Sort streams by median time behind (ascending - fastest first), as scored by the scoring mode.
+        // Streams with no scored race at all have no median and go last.
+        let mut sorted_metrics = metrics;
+        sorted_metrics.sort_by(|a, b| {
+            let unscored = |m: &StreamMetrics| m.total_races == 0 && m.penalized == 0;
+            unscored(a).cmp(&unscored(b))
+                .then(a.median_time_behind_ms.partial_cmp(&b.median_time_behind_ms).unwrap())
+        });
+        
+        RaceSummary {
+            race,
+            noun,
+            stream_count,
+            total_tracked: results.len(),
+            completed_races,
+            partial_results: results.len() - completed_races,
+            open_races: results.iter().filter(|r| r.closed.is_none()).count(),
+            streams: sorted_metrics,
+        }
     }
     
-    async fn calculate_stream_metrics(&self, state: &RefereeState) -> Vec<StreamMetrics> {
+    async fn calculate_stream_metrics(&self, results: &[&RaceResult], state: &RefereeState) -> Vec<StreamMetrics> {
         let mut metrics = Vec::new();
         
-        for stream_name in &state.stream_names {
-            let mut times_behind_winner_ns: Vec<u128> = Vec::new();
+        // Score applied to missed and late races, if the scoring mode penalizes them
+        let timeout_ms = self.scoring.timeout_ms as f64;
+        let penalty_ms = match self.scoring.mode {
+            ScoringMode::Reported => None,
+            ScoringMode::Timeout => Some(timeout_ms),
+            ScoringMode::Infinite => Some(f64::INFINITY),
+        };
+        
+        for stream_name in &self.race_streams(&state.stream_names) {
+            let first_slot = state.streams.get(stream_name).and_then(|s| s.first_slot);
+            let mut times_behind_ms: Vec<f64> = Vec::new();
+            let mut transport_delays_ms: Vec<f64> = Vec::new();
+            let mut provider_delays_ms: Vec<f64> = Vec::new();
             let mut wins = 0;
             let mut races_participated = 0;
+            let mut missed = 0;
+            let mut late = 0;
+            let mut replayed = 0;
+            let mut penalized = 0;
+            let mut duplicates = 0;
+            let mut reorders = 0;
+            let mut max_reorder_depth = 0;
             
-            for result in &state.results {
-                if let Some(&my_time) = result.finish_times.get(stream_name) {
-                    races_participated += 1;
-                    
-                    // Calculate time behind winner in nanoseconds (0 if we won)
-                    let time_behind_ns = my_time.saturating_sub(result.winner_timestamp);
-                    times_behind_winner_ns.push(time_behind_ns);
-                    
-                    if result.winner == *stream_name {
-                        wins += 1;
+            for result in results.iter() {
+                // Every race in the window came from at least one stream, so a race this stream
+                // sat out after it started reporting, or at all if it never has, is a missed delivery
+                let Some(&my_time) = result.finish_times.get(stream_name) else {
+                    if result.replayed.contains_key(stream_name) {
+                        replayed += 1;
+                        continue;
                     }
+                    // Still within its deadline - the stream may yet report
+                    if result.closed.is_none() {
+                        continue;
+                    }
+                    let late_ms = result.late.get(stream_name)
+                        .map(|&at| at.saturating_sub(result.winner_timestamp) as f64 / 1_000_000.0);
+                    let is_late = late_ms.is_some();
+                    let is_missed = !is_late && first_slot.is_none_or(|first| result.key.slot() >= first);
+                    if is_late {
+                        late += 1;
+                    } else if is_missed {
+                        missed += 1;
+                    }
+                    // A late report never scores better than it arrived, and a missed race never better
+                    // than reporting it would have - so dropping a slow update can't beat reporting it
+                    if let Some(penalty) = penalty_ms.filter(|_| is_late || is_missed) {
+                        times_behind_ms.push(penalty.max(late_ms.unwrap_or_else(|| self.missed_time_behind_ms(result))));
+                        penalized += 1;
+                    }
+                    continue;
+                };
+                
+                races_participated += 1;
+                duplicates += result.duplicates.get(stream_name).copied().unwrap_or(0);
+                if let Some(&depth) = result.reorder_depths.get(stream_name) {
+                    reorders += 1;
+                    max_reorder_depth = max_reorder_depth.max(depth);
+                }
+                
+                // Time behind winner in milliseconds (0 if we won)
+                let time_behind_ms = my_time.saturating_sub(result.winner_timestamp) as f64 / 1_000_000.0;
+                match penalty_ms {
+                    Some(penalty) if time_behind_ms > timeout_ms => {
+                        times_behind_ms.push(penalty.max(time_behind_ms));
+                        penalized += 1;
+                    }
+                    _ => times_behind_ms.push(time_behind_ms),
+                }
+                
+                if result.winner == *stream_name {
+                    wins += 1;
+                }
+                
+                // Split latency using the provider's own timestamp, when it sent one
+                if let Some(&created_at) = result.created_at.get(stream_name) {
+                    // Signed - clock skew between us and the provider can make this negative
+                    let transport_ns = my_time as i128 - created_at as i128;
+                    transport_delays_ms.push(transport_ns as f64 / 1_000_000.0);
+                    
+                    let earliest_created_at = result.created_at.values().min().copied().unwrap_or(created_at);
+                    provider_delays_ms.push((created_at - earliest_created_at) as f64 / 1_000_000.0);
                 }
             }
             
-            if races_participated == 0 {
+            // Nothing happened to this stream yet - one that only missed races is still listed, and
+            // ranks by its penalties when the scoring mode penalizes them
+            if times_behind_ms.is_empty() && missed == 0 && late == 0 {
                 continue;
             }
             
-            // Convert to milliseconds for display
-            let times_behind_ms: Vec<f64> = times_behind_winner_ns.iter()
-                .map(|&ns| ns as f64 / 1_000_000.0)
-                .collect();
-            
             // Calculate median in milliseconds
             let median_time_behind = self.calculate_median(&times_behind_ms);
             
             // Calculate percentiles in milliseconds
             let (p90, p95, p99) = self.calculate_percentiles(&times_behind_ms);
             
+            let rtts_ms: Vec<f64> = state.connections.get(stream_name)
+                .map(|connection| connection.rtts_ms.iter().copied().collect())
+                .unwrap_or_default();
+            let has_rtt = !rtts_ms.is_empty();
+            
             metrics.push(StreamMetrics {
                 name: stream_name.clone(),
                 wins,
                 total_races: races_participated,
-                win_rate: (wins as f64 / races_participated as f64) * 100.0,
+                win_rate: (wins as f64 / races_participated.max(1) as f64) * 100.0,
                 median_time_behind_ms: median_time_behind,
                 p90_time_behind_ms: p90,
                 p95_time_behind_ms: p95,
                 p99_time_behind_ms: p99,
+                median_transport_delay_ms: (!transport_delays_ms.is_empty())
+                    .then(|| self.calculate_median(&transport_delays_ms)),
+                median_provider_delay_ms: (!provider_delays_ms.is_empty())
+                    .then(|| self.calculate_median(&provider_delays_ms)),
+                min_rtt_ms: rtts_ms.iter().copied().reduce(f64::min),
+                median_rtt_ms: has_rtt.then(|| self.calculate_median(&rtts_ms)),
+                p99_rtt_ms: has_rtt.then(|| self.calculate_percentiles(&rtts_ms).2),
+                missed,
+                missed_rate: (missed as f64 / (races_participated + missed).max(1) as f64) * 100.0,
+                late,
+                replayed,
+                penalized,
+                duplicates,
+                reorders,
+                max_reorder_depth,
             });
         }
         
         metrics
     }
     
+    // Least a stream that missed a race was behind - the slowest report in it, or the whole
+    // deadline if the race ran out of time waiting
+    fn missed_time_behind_ms(&self, result: &RaceResult) -> f64 {
+        let slowest_ns = result.finish_times.values().max().map_or(0, |&at| at.saturating_sub(result.winner_timestamp));
+        let waited_ns = match result.closed {
+            Some(CloseReason::Deadline) => slowest_ns.max(self.race_deadline_ns),
+            _ => slowest_ns,
+        };
+        waited_ns as f64 / 1_000_000.0
+    }
+    
     fn calculate_median(&self, values: &[f64]) -> f64 {
         if values.is_empty() {
             return 0.0;
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use futures::channel::mpsc::SendError;
+use futures::future::BoxFuture;
+use futures::stream::BoxStream;
+use futures::{FutureExt, Sink, StreamExt};
+use std::pin::Pin;
+use std::time::{Duration, Instant};
+use tracing::info;
+use yellowstone_grpc_proto::prelude::*;
+
+use super::{SlotSource, SourceContext, StreamClosed};
+use crate::client::GrpcClient;
+use crate::config::StreamConfig;
+use crate::handlers::MessageHandler;
+
+// An open Yellowstone subscription
+struct Subscription {
+    // Keeps the client's channel open for as long as the subscription runs
+    _client: Box<dyn Send>,
+    subscribe_tx: Pin<Box<dyn Sink<SubscribeRequest, Error = SendError> + Send>>,
+    stream: BoxStream<'static, Result<SubscribeUpdate, tonic::Status>>,
+}
+
+// Races a Yellowstone gRPC Geyser subscription
+pub struct GrpcSource {
+    config: StreamConfig,
+    request: SubscribeRequest,
+    context: SourceContext,
+    handler: MessageHandler,
+    subscription: Option<Subscription>,
+}
+
+impl GrpcSource {
+    pub fn new(config: StreamConfig, request: SubscribeRequest, context: SourceContext) -> Self {
+        Self {
+            handler: MessageHandler::new(context.stream_id.clone(), context.referee.clone()),
+            config,
+            request,
+            context,
+            subscription: None,
+        }
+    }
+
+    async fn subscribe(&mut self, resume_from: Option<u64>) -> Result<()> {
+        info!("[{}] Connecting to gRPC endpoint: {}", self.config.name, self.config.endpoint);
+        let mut client = GrpcClient::new(self.config.clone()).connect().await?;
+
+        // Subscribe with the request built from the configured races
+        let request = SubscribeRequest {
+            from_slot: resume_from,
+            ..self.request.clone()
+        };
+        let (subscribe_tx, stream) = client
+            .subscribe_with_request(Some(request))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create subscription: {:?}", e))?;
+
+        info!("[{}] Successfully connected to Yellowstone gRPC", self.config.name);
+        self.subscription = Some(Subscription {
+            _client: Box::new(client),
+            subscribe_tx: Box::pin(subscribe_tx),
+            stream: stream.boxed(),
+        });
+        Ok(())
+    }
+
+    async fn race(&mut self) -> Result<()> {
+        let Some(mut subscription) = self.subscription.take() else {
+            return Err(anyhow::anyhow!("Not subscribed"));
+        };
+        info!("[{}] Subscribed to race updates, waiting for messages...", self.context.stream_id);
+
+        // Client pings measure round-trip time over the stream itself
+        let ping_interval = self.context.referee.ping_interval();
+        let mut pings = tokio::time::interval(ping_interval.unwrap_or(Duration::from_secs(86_400)));
+
+        loop {
+            let message = tokio::select! {
+                message = subscription.stream.next() => message,
+                _ = pings.tick(), if ping_interval.is_some() => {
+                    self.handler.send_ping(self.context.now(), &mut subscription.subscribe_tx).await
+                        .map_err(|e| anyhow::anyhow!("Failed to send ping: {}", e))?;
+                    continue;
+                }
+            };
+            let Some(message) = message else {
+                break;
+            };
+
+            // Capture timestamp using high-resolution Instant
+            // This gives us true nanosecond precision
+            let receive_instant = Instant::now();
+            let receive_timestamp = self.context.clock.timestamp(receive_instant);
+
+            // Errors are returned so the caller records them and reconnects
+            match message {
+                Ok(msg) => {
+                    // Server-side creation time, on the same timeline as the receive time
+                    let created_at = msg.created_at.as_ref()
+                        .and_then(|ts| self.context.clock.timestamp_from_proto(ts));
+                    self.handler.handle_message(msg, receive_timestamp, created_at, &mut subscription.subscribe_tx).await
+                        .map_err(|e| anyhow::anyhow!("Error handling message: {}", e))?;
+                }
+                // Keep the status, so the disconnect is counted under its code
+                Err(e) => {
+                    return Err(anyhow::Error::new(e).context("Stream error"));
+                }
+            }
+        }
+
+        // A race stream should never end on its own - treat it as a failure so it reconnects
+        info!("[{}] Stream closed", self.context.stream_id);
+        Err(StreamClosed.into())
+    }
+}
+
+impl SlotSource for GrpcSource {
+    fn connect(&mut self, resume_from: Option<u64>) -> BoxFuture<'_, Result<()>> {
+        self.subscribe(resume_from).boxed()
+    }
+
+    fn run(&mut self) -> BoxFuture<'_, Result<()>> {
+        self.race().boxed()
+    }
+
+    // Highest slot received so far - where a resumed subscription picks up
+    fn resume_slot(&self) -> Option<u64> {
+        self.handler.last_slot()
+    }
+}
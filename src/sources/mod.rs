@@ -0,0 +1,201 @@
+use anyhow::Result;
+use backoff::backoff::Backoff;
+use futures::future::BoxFuture;
+use std::fmt;
+use std::time::Instant;
+use tracing::{error, warn};
+use yellowstone_grpc_proto::prelude::SubscribeRequest;
+
+use crate::config::{ReconnectConfig, StreamConfig, StreamKind};
+use crate::referee::{RaceKey, SharedReferee};
+use crate::SharedClock;
+
+mod grpc;
+mod replay;
+mod shreds;
+mod websocket;
+
+pub use grpc::GrpcSource;
+pub use replay::ReplaySource;
+pub use shreds::ShredSource;
+pub use websocket::WebsocketSource;
+
+// A stream backend that races updates into the referee
+//
+// Sources only connect and report. `run_source` drives them: it reconnects with backoff,
+// tracks the connection and runs the stall watchdog, so a new backend only implements this trait.
+pub trait SlotSource: Send {
+    // Open a connection, replaying from `resume_from` if the source supports it
+    fn connect(&mut self, resume_from: Option<u64>) -> BoxFuture<'_, Result<()>>;
+
+    // Report updates from the open connection until it fails - returning Ok ends the stream for good
+    fn run(&mut self) -> BoxFuture<'_, Result<()>>;
+
+    // Slot a reconnect should resume from - None if the source can't resume
+    fn resume_slot(&self) -> Option<u64> {
+        None
+    }
+}
+
+// What a source reports with: its stream name, the referee and the shared race clock
+#[derive(Clone)]
+pub struct SourceContext {
+    pub stream_id: String,
+    pub referee: SharedReferee,
+    pub clock: SharedClock,
+}
+
+impl SourceContext {
+    pub fn new(stream_id: String, referee: SharedReferee, clock: SharedClock) -> Self {
+        Self { stream_id, referee, clock }
+    }
+
+    // Current time on the race clock - take it as soon as an update is received
+    pub fn now(&self) -> u128 {
+        self.clock.timestamp(Instant::now())
+    }
+
+    pub fn report(&self, key: RaceKey, timestamp: u128, created_at: Option<u128>) {
+        self.referee.send_report(key, self.stream_id.clone(), timestamp, created_at);
+    }
+}
+
+// The watchdog forced a reconnect - the reason is one of check_stale's fixed categories
+#[derive(Debug)]
+struct Stalled(&'static str);
+
+impl fmt::Display for Stalled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stream stalled: {}", self.0)
+    }
+}
+
+impl std::error::Error for Stalled {}
+
+// The provider ended the stream without an error
+#[derive(Debug)]
+pub(crate) struct StreamClosed;
+
+impl fmt::Display for StreamClosed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Stream closed by server")
+    }
+}
+
+impl std::error::Error for StreamClosed {}
+
+// Fixed category a failed connection is counted under, so disconnect reasons group over a long run.
+// Error texts carry details like gRPC metadata that would make every occurrence its own reason.
+fn disconnect_reason(error: &anyhow::Error, connected: bool, resume_rejected: bool) -> String {
+    if let Some(stalled) = error.downcast_ref::<Stalled>() {
+        return stalled.to_string();
+    }
+    if resume_rejected {
+        return "Resume rejected".to_string();
+    }
+    if !connected {
+        return "Connect failed".to_string();
+    }
+    if let Some(status) = error.chain().find_map(|cause| cause.downcast_ref::<tonic::Status>()) {
+        return format!("gRPC {:?}", status.code());
+    }
+    if error.is::<StreamClosed>() {
+        return StreamClosed.to_string();
+    }
+    "Stream error".to_string()
+}
+
+// Builds the source of a custom stream once the race starts
+pub type SourceFactory = Box<dyn FnOnce(SourceContext) -> Box<dyn SlotSource> + Send>;
+
+// The source for a configured stream - custom streams have theirs registered with RaceBuilder::source instead
+pub fn from_config(config: &StreamConfig, request: &SubscribeRequest, context: SourceContext) -> Result<Box<dyn SlotSource>> {
+    Ok(match config.kind {
+        StreamKind::Grpc => Box::new(GrpcSource::new(config.clone(), request.clone(), context)),
+        StreamKind::Websocket => Box::new(WebsocketSource::new(config.clone(), request, context)),
+        StreamKind::Shreds => Box::new(ShredSource::new(config.clone(), context)),
+        StreamKind::Replay => Box::new(ReplaySource::new(config.endpoint.clone(), context)),
+        StreamKind::Custom => {
+            return Err(anyhow::anyhow!("No source registered for custom stream '{}'", config.name));
+        }
+    })
+}
+
+// Keep a source connected, reconnecting with backoff until the reconnect policy gives up
+pub async fn run_source(mut source: Box<dyn SlotSource>, context: SourceContext, reconnect: ReconnectConfig, resume: bool) -> Result<()> {
+    let mut backoff = reconnect.to_backoff();
+    // Set after the provider turned down a resume, so the next attempt subscribes fresh
+    let mut resume_rejected = false;
+
+    loop {
+        // Pick up where the previous connection left off, so the outage's slots are replayed rather than lost
+        let resume_from = source.resume_slot().filter(|_| resume && !resume_rejected);
+
+        let mut connected = false;
+        let error = match connect_and_run(source.as_mut(), &context, resume_from, &mut connected).await {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+
+        // A resumed connection that fails before delivering anything new most likely had its from_slot
+        // rejected (no replay history, or the slot is too old) - retrying it would fail the same way forever
+        let resume_failed = connected && resume_from.is_some() && source.resume_slot() == resume_from;
+        let reason = disconnect_reason(&error, connected, resume_failed);
+        context.referee.send_stream_error(context.stream_id.clone(), reason, context.now());
+
+        // A connection that came up starts the backoff over, so the elapsed time only counts failing attempts
+        if connected {
+            backoff.reset();
+            resume_rejected = resume_failed;
+            if let Some(from_slot) = resume_from.filter(|_| resume_rejected) {
+                warn!(
+                    "[{}] Resuming from slot {} failed, subscribing without from_slot - slots missed during the outage are lost",
+                    context.stream_id,
+                    from_slot
+                );
+            }
+        }
+
+        match backoff.next_backoff() {
+            Some(delay) => {
+                error!("[{}] Subscription failed, retrying in {:?}: {error:#}", context.stream_id, delay);
+                tokio::time::sleep(delay).await;
+            }
+            None => {
+                error!("[{}] Subscription failed, giving up after {}ms of retries: {error:#}", context.stream_id, reconnect.max_elapsed_ms);
+                return Err(error);
+            }
+        }
+    }
+}
+
+// Connect once and race updates until the source fails or stalls
+async fn connect_and_run(
+    source: &mut dyn SlotSource,
+    context: &SourceContext,
+    resume_from: Option<u64>,
+    connected: &mut bool,
+) -> Result<()> {
+    context.referee.send_connecting(context.stream_id.clone(), context.now());
+    source.connect(resume_from).await?;
+
+    *connected = true;
+    context.referee.send_connected(context.stream_id.clone(), context.now(), resume_from);
+
+    // An open connection can go quiet without ever erroring - the watchdog catches that.
+    // It only finishes on a stall, so the source is never cancelled mid-update otherwise.
+    let mut watchdog = tokio::time::interval(context.referee.watchdog_interval());
+    let stalled = async {
+        loop {
+            watchdog.tick().await;
+            if let Some(reason) = context.referee.check_stale(&context.stream_id, context.now()).await {
+                return reason;
+            }
+        }
+    };
+
+    tokio::select! {
+        result = source.run() => result,
+        reason = stalled => Err(Stalled(reason).into()),
+    }
+}
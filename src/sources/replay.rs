@@ -0,0 +1,104 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::Deserialize;
+use std::collections::HashMap;
+use tracing::info;
+
+use super::{SlotSource, SourceContext};
+use crate::referee::RaceKey;
+
+// One race of an NDJSON race export
+#[derive(Deserialize)]
+struct ExportRecord {
+    race: String,
+    slot: u64,
+    streams: HashMap<String, ExportFinish>,
+}
+
+#[derive(Deserialize)]
+struct ExportFinish {
+    timestamp_ns: u128,
+    created_at_ns: Option<u128>,
+}
+
+// A recorded report - slot, race clock timestamp and created_at
+type Recorded = (u64, u128, Option<u128>);
+
+// Replays the slots a stream delivered in an NDJSON race export, at their original race clock times,
+// so a recorded race can be re-run offline, e.g. under another scoring mode
+pub struct ReplaySource {
+    path: String,
+    context: SourceContext,
+    // Loaded on the first connect, then consumed across reconnects
+    reports: Option<Vec<Recorded>>,
+    position: usize,
+}
+
+impl ReplaySource {
+    pub fn new(path: String, context: SourceContext) -> Self {
+        Self {
+            path,
+            context,
+            reports: None,
+            position: 0,
+        }
+    }
+
+    fn load(&mut self) -> Result<()> {
+        if self.reports.is_some() {
+            return Ok(());
+        }
+
+        info!("[{}] Loading recorded slots from {}", self.context.stream_id, self.path);
+        let contents = std::fs::read_to_string(&self.path)
+            .map_err(|e| anyhow::anyhow!("Failed to read replay file {}: {}", self.path, e))?;
+
+        let mut reports = Vec::new();
+        for (index, line) in contents.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let record: ExportRecord = serde_json::from_str(line)
+                .map_err(|e| anyhow::anyhow!("Invalid record on line {} of {}: {}", index + 1, self.path, e))?;
+            if record.race != "slot" {
+                continue;
+            }
+            if let Some(finish) = record.streams.get(&self.context.stream_id) {
+                reports.push((record.slot, finish.timestamp_ns, finish.created_at_ns));
+            }
+        }
+
+        if reports.is_empty() {
+            return Err(anyhow::anyhow!("No slot races recorded for stream '{}' in {}", self.context.stream_id, self.path));
+        }
+
+        // Races are exported as they close, not in the order they were reported
+        reports.sort_by_key(|(_, timestamp, _)| *timestamp);
+        info!("[{}] Replaying {} recorded slots", self.context.stream_id, reports.len());
+        self.reports = Some(reports);
+        Ok(())
+    }
+
+    async fn replay(&mut self) -> Result<()> {
+        let Some(reports) = &self.reports else {
+            return Err(anyhow::anyhow!("Replay file not loaded"));
+        };
+
+        while let Some(&(slot, timestamp, created_at)) = reports.get(self.position) {
+            tokio::time::sleep_until(self.context.clock.instant(timestamp).into()).await;
+            self.context.report(RaceKey::Slot(slot), timestamp, created_at);
+            self.position += 1;
+        }
+
+        info!("[{}] Replay finished", self.context.stream_id);
+        Ok(())
+    }
+}
+
+impl SlotSource for ReplaySource {
+    fn connect(&mut self, _resume_from: Option<u64>) -> BoxFuture<'_, Result<()>> {
+        futures::future::ready(self.load()).boxed()
+    }
+
+    fn run(&mut self) -> BoxFuture<'_, Result<()>> {
+        self.replay().boxed()
+    }
+}
@@ -0,0 +1,212 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use std::collections::BTreeSet;
+use tokio::net::UdpSocket;
+use tracing::{debug, info};
+
+use super::{SlotSource, SourceContext};
+use crate::config::StreamConfig;
+use crate::referee::RaceKey;
+
+// Shred common header: signature (64 bytes), shred variant (1), slot (u64 LE), index (u32 LE), ...
+const VARIANT_OFFSET: usize = 64;
+const SLOT_OFFSET: usize = 65;
+
+// Largest shred is 1228 bytes - anything up to a full datagram is read so oversized packets aren't truncated silently
+const MAX_PACKET_SIZE: usize = 2048;
+
+// Slots reported this far below the highest slot are forgotten - older shreds are ignored outright
+const REPORTED_SLOT_WINDOW: u64 = 1000;
+
+// A new slot this far past the race's leader, or this stream's highest slot, is noise that happens to look like a shred
+const MAX_SLOTS_AHEAD: u64 = 1000;
+
+// Slots held before anything is reported - noise beyond this pushes out the oldest
+const MAX_UNCONFIRMED_SLOTS: usize = 16;
+
+// Packet size of a shred variant - legacy data/code and merkle code shreds (plain 0x4_, chained 0x6_,
+// chained and resigned 0x7_) are 1228 bytes, merkle data shreds (0x8_, 0x9_, 0xb_) 1203
+fn shred_size(variant: u8) -> Option<usize> {
+    match (variant, variant >> 4) {
+        (0xa5 | 0x5a, _) | (_, 0x4 | 0x6 | 0x7) => Some(1228),
+        (_, 0x8 | 0x9 | 0xb) => Some(1203),
+        _ => None,
+    }
+}
+
+// Slot of a raw shred packet, or None if the packet isn't a shred
+fn shred_slot(packet: &[u8]) -> Option<u64> {
+    if packet.get(VARIANT_OFFSET).and_then(|variant| shred_size(*variant)) != Some(packet.len()) {
+        return None;
+    }
+    let slot = packet[SLOT_OFFSET..SLOT_OFFSET + 8].try_into().ok()?;
+    Some(u64::from_le_bytes(slot))
+}
+
+// Races the first shred of every slot, e.g. from a Jito ShredStream proxy, against the other streams
+pub struct ShredSource {
+    config: StreamConfig,
+    context: SourceContext,
+    socket: Option<UdpSocket>,
+    // Slots already reported - a slot arrives as hundreds of shreds
+    reported: BTreeSet<u64>,
+    // Slots and receive times held until another slot or the race's leader shows they're plausible
+    unconfirmed: Vec<(u64, u128)>,
+    invalid_packets: u64,
+}
+
+impl ShredSource {
+    pub fn new(config: StreamConfig, context: SourceContext) -> Self {
+        Self {
+            config,
+            context,
+            socket: None,
+            reported: BTreeSet::new(),
+            unconfirmed: Vec::new(),
+            invalid_packets: 0,
+        }
+    }
+
+    // Bind the local UDP port shreds are forwarded to
+    async fn bind(&mut self) -> Result<()> {
+        let addr = self.config.shred_listen_addr()?;
+        let socket = UdpSocket::bind(addr)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to bind shred listener on {}: {}", addr, e))?;
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    async fn race(&mut self) -> Result<()> {
+        let Some(socket) = self.socket.take() else {
+            return Err(anyhow::anyhow!("Not listening"));
+        };
+        info!("[{}] Listening for shreds on {}, waiting for packets...", self.context.stream_id, socket.local_addr()?);
+
+        // UDP never errors when the sender goes away - only the watchdog notices
+        let mut buffer = [0u8; MAX_PACKET_SIZE];
+
+        loop {
+            let (len, _) = socket.recv_from(&mut buffer).await
+                .map_err(|e| anyhow::anyhow!("Stream error: {}", e))?;
+            let receive_timestamp = self.context.now();
+
+            let Some(slot) = shred_slot(&buffer[..len]) else {
+                self.invalid_packets += 1;
+                debug!("[{}] Ignoring {} byte packet that isn't a shred ({} so far)", self.context.stream_id, len, self.invalid_packets);
+                continue;
+            };
+
+            if !self.is_new_slot(slot) {
+                continue;
+            }
+
+            // A single bogus slot far ahead would become the highest slot, and the race's leader
+            let leader = self.context.referee.leader_slot().await.max(self.reported.last().copied());
+            let Some(leader) = leader else {
+                self.hold_first_slot(slot, receive_timestamp);
+                continue;
+            };
+            for (held, held_timestamp) in std::mem::take(&mut self.unconfirmed) {
+                if held > leader.saturating_add(MAX_SLOTS_AHEAD) {
+                    self.ignore_slot(held, leader);
+                } else if self.is_new_slot(held) {
+                    self.report_slot(held, held_timestamp);
+                }
+            }
+            if !self.is_new_slot(slot) {
+                continue;
+            }
+            if slot > leader.saturating_add(MAX_SLOTS_AHEAD) {
+                self.ignore_slot(slot, leader);
+                continue;
+            }
+            self.report_slot(slot, receive_timestamp);
+        }
+    }
+
+    // With nothing reported anywhere yet there is no leader to judge a slot by - slots are held until
+    // a second one close to them arrives, then reported in arrival order and the rest dropped as noise
+    fn hold_first_slot(&mut self, slot: u64, receive_timestamp: u128) {
+        if self.unconfirmed.iter().any(|(held, _)| *held == slot) {
+            return;
+        }
+        if !self.unconfirmed.iter().any(|(held, _)| held.abs_diff(slot) <= MAX_SLOTS_AHEAD) {
+            if self.unconfirmed.len() == MAX_UNCONFIRMED_SLOTS {
+                let (dropped, _) = self.unconfirmed.remove(0);
+                self.invalid_packets += 1;
+                debug!("[{}] Ignoring unconfirmed shred slot {} ({} so far)", self.context.stream_id, dropped, self.invalid_packets);
+            }
+            self.unconfirmed.push((slot, receive_timestamp));
+            return;
+        }
+
+        for (held, held_timestamp) in std::mem::take(&mut self.unconfirmed) {
+            if held.abs_diff(slot) <= MAX_SLOTS_AHEAD {
+                self.report_slot(held, held_timestamp);
+            } else {
+                self.invalid_packets += 1;
+                debug!(
+                    "[{}] Ignoring unconfirmed shred slot {}, {} slots from slot {} ({} so far)",
+                    self.context.stream_id,
+                    held,
+                    held.abs_diff(slot),
+                    slot,
+                    self.invalid_packets
+                );
+            }
+        }
+        self.report_slot(slot, receive_timestamp);
+    }
+
+    // Drop a slot too far past the leader to be real
+    fn ignore_slot(&mut self, slot: u64, leader: u64) {
+        self.invalid_packets += 1;
+        debug!(
+            "[{}] Ignoring shred for slot {}, {} slots past the leader ({} so far)",
+            self.context.stream_id,
+            slot,
+            slot - leader,
+            self.invalid_packets
+        );
+    }
+
+    // Report the first shred of a slot to the referee
+    fn report_slot(&mut self, slot: u64, receive_timestamp: u128) {
+        self.record_slot(slot);
+
+        info!(
+            "[{}] First shred: slot={}, received_at={}ms ({}ns)",
+            self.context.stream_id,
+            slot,
+            receive_timestamp / 1_000_000,
+            receive_timestamp
+        );
+        // Shreds carry no timestamp
+        self.context.report(RaceKey::Slot(slot), receive_timestamp, None);
+    }
+
+    // Whether this is the first shred of a slot recent enough to report
+    fn is_new_slot(&self, slot: u64) -> bool {
+        let highest = self.reported.last().copied().unwrap_or(0);
+        slot.saturating_add(REPORTED_SLOT_WINDOW) >= highest && !self.reported.contains(&slot)
+    }
+
+    // Remember a reported slot, forgetting those that fell out of the window
+    fn record_slot(&mut self, slot: u64) {
+        self.reported.insert(slot);
+        let highest = self.reported.last().copied().unwrap_or(slot);
+        self.reported = self.reported.split_off(&highest.saturating_sub(REPORTED_SLOT_WINDOW));
+    }
+}
+
+impl SlotSource for ShredSource {
+    fn connect(&mut self, _resume_from: Option<u64>) -> BoxFuture<'_, Result<()>> {
+        self.bind().boxed()
+    }
+
+    fn run(&mut self) -> BoxFuture<'_, Result<()>> {
+        self.race().boxed()
+    }
+}
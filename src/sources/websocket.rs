@@ -0,0 +1,190 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+use futures::{FutureExt, SinkExt, StreamExt};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::{debug, info, warn};
+use yellowstone_grpc_proto::prelude::{SlotStatus, SubscribeRequest};
+
+use super::{SlotSource, SourceContext, StreamClosed};
+use crate::config::StreamConfig;
+use crate::referee::{RaceKey, RaceKind};
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+// JSON-RPC response or subscription notification
+#[derive(Deserialize)]
+struct RpcMessage {
+    id: Option<u64>,
+    result: Option<serde_json::Value>,
+    error: Option<serde_json::Value>,
+    method: Option<String>,
+    params: Option<NotificationParams>,
+}
+
+#[derive(Deserialize)]
+struct NotificationParams {
+    result: serde_json::Value,
+}
+
+// slotNotification result
+#[derive(Deserialize)]
+struct SlotInfo {
+    slot: u64,
+}
+
+// slotsUpdatesNotification result
+#[derive(Deserialize)]
+struct SlotUpdate {
+    slot: u64,
+    // Node-side time of the update in Unix milliseconds
+    timestamp: Option<u64>,
+    #[serde(rename = "type")]
+    update_type: String,
+}
+
+// slotsUpdatesSubscribe update types, as the matching Yellowstone slot status
+fn slot_status(update_type: &str) -> Option<SlotStatus> {
+    match update_type {
+        "firstShredReceived" => Some(SlotStatus::SlotFirstShredReceived),
+        "completed" => Some(SlotStatus::SlotCompleted),
+        "createdBank" => Some(SlotStatus::SlotCreatedBank),
+        // Yellowstone reports a slot as processed once its bank is frozen
+        "frozen" => Some(SlotStatus::SlotProcessed),
+        "dead" => Some(SlotStatus::SlotDead),
+        "optimisticConfirmation" => Some(SlotStatus::SlotConfirmed),
+        "root" => Some(SlotStatus::SlotFinalized),
+        _ => None,
+    }
+}
+
+// Races slots from a Solana JSON-RPC PubSub WebSocket through the same referee path as the gRPC streams
+pub struct WebsocketSource {
+    config: StreamConfig,
+    context: SourceContext,
+    // PubSub only has slot subscriptions - these are the slot races the request asks for
+    races: Vec<RaceKind>,
+    socket: Option<Socket>,
+}
+
+impl WebsocketSource {
+    pub fn new(config: StreamConfig, request: &SubscribeRequest, context: SourceContext) -> Self {
+        let races = [RaceKind::Slot, RaceKind::SlotStatus]
+            .into_iter()
+            .filter(|kind| request.slots.contains_key(&kind.to_string()))
+            .collect();
+
+        Self {
+            config,
+            context,
+            races,
+            socket: None,
+        }
+    }
+
+    // Open the socket, sending the access token as x-token like the gRPC streams do, and subscribe
+    async fn subscribe(&mut self) -> Result<()> {
+        info!("[{}] Connecting to WebSocket endpoint: {}", self.config.name, self.config.endpoint);
+        let mut request = self.config.endpoint.as_str().into_client_request()?;
+        if let Some(token) = &self.config.access_token {
+            request.headers_mut().insert("x-token", token.parse()?);
+        }
+        let (mut socket, _response) = tokio_tungstenite::connect_async(request).await?;
+
+        for (index, kind) in self.races.iter().enumerate() {
+            let method = match kind {
+                RaceKind::Slot => "slotSubscribe",
+                _ => "slotsUpdatesSubscribe",
+            };
+            let request = json!({ "jsonrpc": "2.0", "id": index + 1, "method": method });
+            socket
+                .send(Message::Text(request.to_string().into()))
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to send {}: {}", method, e))?;
+        }
+
+        info!("[{}] Successfully connected to Solana PubSub WebSocket", self.config.name);
+        self.socket = Some(socket);
+        Ok(())
+    }
+
+    async fn race(&mut self) -> Result<()> {
+        let Some(mut socket) = self.socket.take() else {
+            return Err(anyhow::anyhow!("Not subscribed"));
+        };
+        info!("[{}] Subscribed to slot updates over WebSocket, waiting for messages...", self.context.stream_id);
+
+        while let Some(message) = socket.next().await {
+            let receive_timestamp = self.context.now();
+
+            // Pings are answered by tungstenite itself
+            match message.map_err(|e| anyhow::anyhow!("Stream error: {}", e))? {
+                Message::Text(text) => self.handle_text(text.as_str(), receive_timestamp)?,
+                Message::Close(frame) => {
+                    return Err(anyhow::anyhow!("WebSocket closed by server: {:?}", frame));
+                }
+                _ => {}
+            }
+        }
+
+        // A race stream should never end on its own - treat it as a failure so it reconnects
+        info!("[{}] Stream closed", self.context.stream_id);
+        Err(StreamClosed.into())
+    }
+
+    fn handle_text(&self, text: &str, receive_timestamp: u128) -> Result<()> {
+        let message: RpcMessage = serde_json::from_str(text)
+            .map_err(|e| anyhow::anyhow!("Invalid JSON-RPC message: {}", e))?;
+
+        if let Some(error) = message.error {
+            return Err(anyhow::anyhow!("Subscription request {:?} failed: {}", message.id, error));
+        }
+
+        let (Some(method), Some(params)) = (message.method, message.params) else {
+            // Subscription confirmation
+            debug!("[{}] Request {:?} subscribed as {:?}", self.context.stream_id, message.id, message.result);
+            return Ok(());
+        };
+
+        match method.as_str() {
+            "slotNotification" => {
+                let info: SlotInfo = serde_json::from_value(params.result)?;
+                info!(
+                    "[{}] Slot notification: slot={}, received_at={}ms ({}ns)",
+                    self.context.stream_id,
+                    info.slot,
+                    receive_timestamp / 1_000_000,
+                    receive_timestamp
+                );
+                // slotSubscribe carries no server-side timestamp
+                self.context.report(RaceKey::Slot(info.slot), receive_timestamp, None);
+            }
+            "slotsUpdatesNotification" => {
+                let update: SlotUpdate = serde_json::from_value(params.result)?;
+                let Some(status) = slot_status(&update.update_type) else {
+                    debug!("[{}] Ignoring slot update type {}", self.context.stream_id, update.update_type);
+                    return Ok(());
+                };
+                let created_at = update.timestamp.and_then(|ms| self.context.clock.timestamp_from_unix_ms(ms));
+                self.context.report(RaceKey::SlotStatus { slot: update.slot, status }, receive_timestamp, created_at);
+            }
+            other => warn!("[{}] Unexpected notification: {}", self.context.stream_id, other),
+        }
+
+        Ok(())
+    }
+}
+
+impl SlotSource for WebsocketSource {
+    fn connect(&mut self, _resume_from: Option<u64>) -> BoxFuture<'_, Result<()>> {
+        self.subscribe().boxed()
+    }
+
+    fn run(&mut self) -> BoxFuture<'_, Result<()>> {
+        self.race().boxed()
+    }
+}
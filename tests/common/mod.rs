@@ -0,0 +1,727 @@
+//! In-process mock Yellowstone Geyser server and a harness that runs the
+//! speedrace binary against it.
+
+#![allow(dead_code)]
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use futures::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::Child;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::{ReceiverStream, TcpListenerStream};
+use tokio_tungstenite::tungstenite::Message;
+use tonic::{Request, Response, Status, Streaming};
+use yellowstone_grpc_proto::geyser::geyser_server::{Geyser, GeyserServer};
+use yellowstone_grpc_proto::prelude::*;
+use yellowstone_grpc_proto::prost_types::Timestamp;
+
+/// Slot schedule shared by every mock endpoint, so per-stream delays are
+/// measured against the same "chain".
+#[derive(Clone, Copy)]
+pub struct MockChain {
+    pub start: Instant,
+    pub first_slot: u64,
+    pub slot_count: u64,
+    pub slot_interval: Duration,
+}
+
+impl MockChain {
+    /// A chain whose first slot is produced after `lead` - long enough for
+    /// the binary to start and connect to every endpoint.
+    pub fn new(lead: Duration, slot_count: u64, slot_interval: Duration) -> Self {
+        Self {
+            start: Instant::now() + lead,
+            first_slot: 1_000,
+            slot_count,
+            slot_interval,
+        }
+    }
+
+    fn scheduled_at(&self, index: u64) -> Instant {
+        self.start + self.slot_interval * index as u32
+    }
+}
+
+/// How one mock endpoint misbehaves. Slots are given as chain indices
+/// (0 = first slot).
+#[derive(Clone, Default)]
+pub struct StreamBehavior {
+    /// Provider-side delay: applied before `created_at` is stamped.
+    pub delay: Duration,
+    /// Network delay: applied after `created_at` is stamped.
+    pub transport_delay: Duration,
+    pub drops: HashSet<u64>,
+    pub duplicates: HashSet<u64>,
+    /// Each listed slot is sent `slow_delay` late, without holding up the rest.
+    pub slow: HashSet<u64>,
+    pub slow_delay: Duration,
+    /// Each listed slot is held back and sent right after the next one.
+    pub reorders: HashSet<u64>,
+    /// End the stream with an error right after this slot (once).
+    pub disconnect_after: Option<u64>,
+    /// Keep the stream open but stop sending right after this slot (once).
+    pub stall_after: Option<u64>,
+    /// Fail any subscription with `from_slot` set, like a provider without
+    /// replay history.
+    pub reject_from_slot: bool,
+    /// Extra delay on confirmed slot statuses (`slot_status` race).
+    pub confirm_delay: Duration,
+    /// Report a different blockhash for this slot's block and block meta, as
+    /// if the stream followed a fork.
+    pub fork_at: Option<u64>,
+    /// Accept subscriptions but never send an update.
+    pub silent: bool,
+}
+
+impl StreamBehavior {
+    pub fn delayed(delay_ms: u64) -> Self {
+        Self {
+            delay: Duration::from_millis(delay_ms),
+            ..Default::default()
+        }
+    }
+}
+
+struct MockGeyser {
+    chain: MockChain,
+    behavior: StreamBehavior,
+    disconnected: Arc<AtomicBool>,
+    stalled: Arc<AtomicBool>,
+}
+
+fn slot_update(slot: u64, filters: &[String], created_at: SystemTime) -> SubscribeUpdate {
+    SubscribeUpdate {
+        filters: filters.to_vec(),
+        created_at: Some(Timestamp::from(created_at)),
+        update_oneof: Some(subscribe_update::UpdateOneof::Slot(SubscribeUpdateSlot {
+            slot,
+            parent: Some(slot - 1),
+            status: SlotStatus::SlotProcessed as i32,
+            dead_error: None,
+        })),
+    }
+}
+
+/// Where confirmed statuses, block metas and full blocks land within a slot,
+/// after its processed status, transactions and account updates.
+const CONFIRMED_OFFSET: Duration = Duration::from_millis(10);
+const BLOCK_META_OFFSET: Duration = Duration::from_millis(5);
+const BLOCK_OFFSET: Duration = Duration::from_millis(20);
+
+/// Transactions and account writes every slot carries.
+const UPDATES_PER_SLOT: u64 = 2;
+
+/// Fixed pubkey of the single account every slot writes to.
+const MOCK_ACCOUNT: [u8; 32] = [7; 32];
+
+/// Signature of the `n`th transaction of a slot.
+pub fn mock_signature(slot: u64, n: u64) -> String {
+    let mut signature = vec![n as u8 + 1; 64];
+    signature[..8].copy_from_slice(&slot.to_le_bytes());
+    bs58::encode(signature).into_string()
+}
+
+fn mock_blockhash(slot: u64, forked: bool) -> String {
+    if forked { format!("fork-{}", slot) } else { format!("hash-{}", slot) }
+}
+
+/// Names of the filters a subscription asked for, other than the plain slot
+/// filter - each gets a scripted update stream.
+#[derive(Clone, Default)]
+struct ScriptedFilters {
+    slot_status: Option<String>,
+    transactions: Option<String>,
+    accounts: Option<String>,
+    blocks: Option<String>,
+    blocks_meta: Option<String>,
+}
+
+impl ScriptedFilters {
+    fn from_request(request: &SubscribeRequest) -> Self {
+        Self {
+            slot_status: request.slots.iter().find(|(_, f)| f.interslot_updates == Some(true)).map(|(name, _)| name.clone()),
+            transactions: request.transactions.keys().next().cloned(),
+            accounts: request.accounts.keys().next().cloned(),
+            blocks: request.blocks.keys().next().cloned(),
+            blocks_meta: request.blocks_meta.keys().next().cloned(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.slot_status.is_none()
+            && self.transactions.is_none()
+            && self.accounts.is_none()
+            && self.blocks.is_none()
+            && self.blocks_meta.is_none()
+    }
+
+    /// Updates for one slot, each with its filter name and offset into the slot.
+    fn updates(&self, slot: u64, index: u64, behavior: &StreamBehavior) -> Vec<(Duration, String, subscribe_update::UpdateOneof)> {
+        use subscribe_update::UpdateOneof;
+
+        let mut updates = Vec::new();
+        let forked = behavior.fork_at == Some(index);
+
+        if let Some(name) = &self.slot_status {
+            let status = |status: SlotStatus| {
+                UpdateOneof::Slot(SubscribeUpdateSlot { slot, parent: Some(slot - 1), status: status as i32, dead_error: None })
+            };
+            updates.push((Duration::ZERO, name.clone(), status(SlotStatus::SlotProcessed)));
+            updates.push((CONFIRMED_OFFSET + behavior.confirm_delay, name.clone(), status(SlotStatus::SlotConfirmed)));
+        }
+        for n in 0..UPDATES_PER_SLOT {
+            if let Some(name) = &self.transactions {
+                let signature = bs58::decode(mock_signature(slot, n)).into_vec().unwrap();
+                let transaction = UpdateOneof::Transaction(SubscribeUpdateTransaction {
+                    transaction: Some(SubscribeUpdateTransactionInfo { signature, index: n, ..Default::default() }),
+                    slot,
+                });
+                updates.push((Duration::ZERO, name.clone(), transaction));
+            }
+            if let Some(name) = &self.accounts {
+                let account = UpdateOneof::Account(SubscribeUpdateAccount {
+                    account: Some(SubscribeUpdateAccountInfo {
+                        pubkey: MOCK_ACCOUNT.to_vec(),
+                        write_version: slot * UPDATES_PER_SLOT + n,
+                        ..Default::default()
+                    }),
+                    slot,
+                    is_startup: false,
+                });
+                updates.push((Duration::ZERO, name.clone(), account));
+            }
+        }
+        if let Some(name) = &self.blocks_meta {
+            let meta = UpdateOneof::BlockMeta(SubscribeUpdateBlockMeta {
+                slot,
+                blockhash: mock_blockhash(slot, forked),
+                parent_slot: slot - 1,
+                ..Default::default()
+            });
+            updates.push((BLOCK_META_OFFSET, name.clone(), meta));
+        }
+        if let Some(name) = &self.blocks {
+            let block = UpdateOneof::Block(SubscribeUpdateBlock {
+                slot,
+                blockhash: mock_blockhash(slot, forked),
+                parent_slot: slot - 1,
+                ..Default::default()
+            });
+            updates.push((BLOCK_OFFSET, name.clone(), block));
+        }
+
+        updates.sort_by_key(|(offset, _, _)| *offset);
+        updates
+    }
+}
+
+impl MockGeyser {
+    /// Scripted slot status, transaction, account and block updates on the
+    /// chain's schedule, delayed by `behavior.delay`. The slot anomalies
+    /// (drops, duplicates, disconnects, ...) only apply to the slot stream.
+    async fn stream_scripted(
+        chain: MockChain,
+        behavior: StreamBehavior,
+        filters: ScriptedFilters,
+        tx: mpsc::Sender<Result<SubscribeUpdate, Status>>,
+    ) {
+        let subscribed_at = Instant::now();
+
+        for index in 0..chain.slot_count {
+            let slot_at = chain.scheduled_at(index) + behavior.delay;
+            if slot_at < subscribed_at {
+                continue;
+            }
+
+            for (offset, name, update) in filters.updates(chain.first_slot + index, index, &behavior) {
+                tokio::time::sleep_until((slot_at + offset).into()).await;
+                let update = SubscribeUpdate {
+                    filters: vec![name],
+                    created_at: Some(Timestamp::from(SystemTime::now())),
+                    update_oneof: Some(update),
+                };
+                if tx.send(Ok(update)).await.is_err() {
+                    return;
+                }
+            }
+        }
+
+        tx.closed().await;
+    }
+
+    async fn stream_slots(
+        chain: MockChain,
+        behavior: StreamBehavior,
+        disconnected: Arc<AtomicBool>,
+        stalled: Arc<AtomicBool>,
+        filters: Vec<String>,
+        from_slot: Option<u64>,
+        tx: mpsc::Sender<Result<SubscribeUpdate, Status>>,
+    ) {
+        // Yellowstone accepts the subscription, then fails the stream when it can't replay
+        if let Some(from) = from_slot.filter(|_| behavior.reject_from_slot) {
+            let _ = tx.send(Err(Status::invalid_argument(format!("broadcast from {} is not available", from)))).await;
+            return;
+        }
+
+        let subscribed_at = Instant::now();
+        let mut held: Option<(u64, SystemTime)> = None;
+
+        for index in 0..chain.slot_count {
+            let send_at = chain.scheduled_at(index) + behavior.delay;
+
+            // Slots produced before this subscription started are lost,
+            // unless `from_slot` asked for them to be replayed right away
+            if send_at < subscribed_at {
+                if from_slot.is_none_or(|from| chain.first_slot + index < from) {
+                    continue;
+                }
+            } else {
+                tokio::time::sleep_until(send_at.into()).await;
+            }
+            let created_at = SystemTime::now();
+            if !behavior.transport_delay.is_zero() {
+                tokio::time::sleep(behavior.transport_delay).await;
+            }
+
+            let slot = chain.first_slot + index;
+            if behavior.drops.contains(&index) {
+                continue;
+            }
+            if behavior.slow.contains(&index) {
+                let (tx, filters) = (tx.clone(), filters.clone());
+                tokio::spawn(async move {
+                    tokio::time::sleep(behavior.slow_delay).await;
+                    let _ = tx.send(Ok(slot_update(slot, &filters, created_at))).await;
+                });
+                continue;
+            }
+            if behavior.reorders.contains(&index) && held.is_none() {
+                held = Some((slot, created_at));
+                continue;
+            }
+
+            let copies = if behavior.duplicates.contains(&index) { 2 } else { 1 };
+            for _ in 0..copies {
+                if tx.send(Ok(slot_update(slot, &filters, created_at))).await.is_err() {
+                    return;
+                }
+            }
+            if let Some((held_slot, held_created_at)) = held.take() {
+                if tx.send(Ok(slot_update(held_slot, &filters, held_created_at))).await.is_err() {
+                    return;
+                }
+            }
+
+            if behavior.disconnect_after == Some(index) && !disconnected.swap(true, Ordering::SeqCst) {
+                let _ = tx.send(Err(Status::unavailable("mock disconnect"))).await;
+                return;
+            }
+            if behavior.stall_after == Some(index) && !stalled.swap(true, Ordering::SeqCst) {
+                break;
+            }
+        }
+
+        // Keep the stream open until the client goes away
+        tx.closed().await;
+    }
+}
+
+#[tonic::async_trait]
+impl Geyser for MockGeyser {
+    type SubscribeStream = ReceiverStream<Result<SubscribeUpdate, Status>>;
+
+    async fn subscribe(
+        &self,
+        request: Request<Streaming<SubscribeRequest>>,
+    ) -> Result<Response<Self::SubscribeStream>, Status> {
+        let mut incoming = request.into_inner();
+        let subscribe_request = incoming
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("missing subscribe request"))?;
+        let slot_filters: Vec<String> = subscribe_request.slots.iter()
+            .filter(|(_, filter)| filter.interslot_updates != Some(true))
+            .map(|(name, _)| name.clone())
+            .collect();
+        let scripted = ScriptedFilters::from_request(&subscribe_request);
+        let from_slot = subscribe_request.from_slot;
+
+        // Answer pings and ignore any other later requests
+        let (tx, rx) = mpsc::channel(1024);
+        let pong_tx = tx.clone();
+        tokio::spawn(async move {
+            while let Ok(Some(request)) = incoming.message().await {
+                if let Some(ping) = request.ping {
+                    let pong = SubscribeUpdate {
+                        filters: vec![],
+                        created_at: None,
+                        update_oneof: Some(subscribe_update::UpdateOneof::Pong(SubscribeUpdatePong { id: ping.id })),
+                    };
+                    if pong_tx.send(Ok(pong)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        if self.behavior.silent {
+            return Ok(Response::new(ReceiverStream::new(rx)));
+        }
+        if !scripted.is_empty() {
+            tokio::spawn(Self::stream_scripted(self.chain, self.behavior.clone(), scripted, tx.clone()));
+        }
+        if !slot_filters.is_empty() {
+            tokio::spawn(Self::stream_slots(
+                self.chain,
+                self.behavior.clone(),
+                self.disconnected.clone(),
+                self.stalled.clone(),
+                slot_filters,
+                from_slot,
+                tx,
+            ));
+        }
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn subscribe_replay_info(
+        &self,
+        _request: Request<SubscribeReplayInfoRequest>,
+    ) -> Result<Response<SubscribeReplayInfoResponse>, Status> {
+        Err(Status::unimplemented("mock"))
+    }
+
+    async fn ping(&self, _request: Request<PingRequest>) -> Result<Response<PongResponse>, Status> {
+        Err(Status::unimplemented("mock"))
+    }
+
+    async fn get_latest_blockhash(
+        &self,
+        _request: Request<GetLatestBlockhashRequest>,
+    ) -> Result<Response<GetLatestBlockhashResponse>, Status> {
+        Err(Status::unimplemented("mock"))
+    }
+
+    async fn get_block_height(
+        &self,
+        _request: Request<GetBlockHeightRequest>,
+    ) -> Result<Response<GetBlockHeightResponse>, Status> {
+        Err(Status::unimplemented("mock"))
+    }
+
+    async fn get_slot(&self, _request: Request<GetSlotRequest>) -> Result<Response<GetSlotResponse>, Status> {
+        Err(Status::unimplemented("mock"))
+    }
+
+    async fn is_blockhash_valid(
+        &self,
+        _request: Request<IsBlockhashValidRequest>,
+    ) -> Result<Response<IsBlockhashValidResponse>, Status> {
+        Err(Status::unimplemented("mock"))
+    }
+
+    async fn get_version(
+        &self,
+        _request: Request<GetVersionRequest>,
+    ) -> Result<Response<GetVersionResponse>, Status> {
+        Err(Status::unimplemented("mock"))
+    }
+}
+
+/// Start a mock endpoint on a random local port and return its address.
+pub async fn spawn_mock(chain: MockChain, behavior: StreamBehavior) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let service = MockGeyser {
+        chain,
+        behavior,
+        disconnected: Arc::new(AtomicBool::new(false)),
+        stalled: Arc::new(AtomicBool::new(false)),
+    };
+
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(GeyserServer::new(service))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+/// Start a mock Solana PubSub WebSocket serving `slotSubscribe` and
+/// `slotsUpdatesSubscribe` on the chain's schedule, delayed by `behavior.delay`.
+pub async fn spawn_ws_mock(chain: MockChain, behavior: StreamBehavior) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        while let Ok((tcp, _)) = listener.accept().await {
+            tokio::spawn(serve_ws(chain, behavior.clone(), tcp));
+        }
+    });
+
+    addr
+}
+
+/// `slotsUpdatesNotification` types the mock sends for every slot, in order -
+/// including one no validator sends, which the stream must ignore.
+pub const WS_SLOT_UPDATE_TYPES: [&str; 7] =
+    ["firstShredReceived", "createdBank", "completed", "frozen", "optimisticConfirmation", "root", "unknownStage"];
+
+async fn serve_ws(chain: MockChain, behavior: StreamBehavior, tcp: TcpStream) {
+    let Ok(socket) = tokio_tungstenite::accept_async(tcp).await else {
+        return;
+    };
+    let (mut sink, mut incoming) = socket.split();
+
+    // Every subscription writes through one sink
+    let (tx, mut rx) = mpsc::unbounded_channel::<Value>();
+    tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            if sink.send(Message::Text(message.to_string().into())).await.is_err() {
+                return;
+            }
+        }
+    });
+
+    // Confirm each subscription before any of its notifications, until the client goes away
+    let mut subscription = 0;
+    while let Some(Ok(message)) = incoming.next().await {
+        let Message::Text(text) = message else {
+            continue;
+        };
+        let request: Value = serde_json::from_str(text.as_str()).unwrap();
+        let method = request["method"].as_str().unwrap().to_string();
+        assert!(method == "slotSubscribe" || method == "slotsUpdatesSubscribe", "unexpected request {}", method);
+        if tx.send(json!({ "jsonrpc": "2.0", "result": subscription, "id": request["id"] })).is_err() {
+            return;
+        }
+        tokio::spawn(notify_ws(chain, behavior.clone(), method, subscription, tx.clone()));
+        subscription += 1;
+    }
+}
+
+async fn notify_ws(chain: MockChain, behavior: StreamBehavior, method: String, subscription: u64, tx: mpsc::UnboundedSender<Value>) {
+    let subscribed_at = Instant::now();
+    for index in 0..chain.slot_count {
+        let send_at = chain.scheduled_at(index) + behavior.delay;
+        if send_at < subscribed_at {
+            continue;
+        }
+        tokio::time::sleep_until(send_at.into()).await;
+
+        let slot = chain.first_slot + index;
+        let notifications = if method == "slotSubscribe" {
+            vec![json!({
+                "jsonrpc": "2.0",
+                "method": "slotNotification",
+                "params": { "result": { "parent": slot - 1, "root": slot - 32, "slot": slot }, "subscription": subscription },
+            })]
+        } else {
+            let timestamp = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap().as_millis() as u64;
+            WS_SLOT_UPDATE_TYPES.iter()
+                .map(|update_type| json!({
+                    "jsonrpc": "2.0",
+                    "method": "slotsUpdatesNotification",
+                    "params": { "result": { "slot": slot, "timestamp": timestamp, "type": update_type }, "subscription": subscription },
+                }))
+                .collect()
+        };
+        for notification in notifications {
+            if tx.send(notification).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// A free local UDP address for a shred stream to listen on.
+pub fn free_udp_addr() -> SocketAddr {
+    std::net::UdpSocket::bind("127.0.0.1:0").unwrap().local_addr().unwrap()
+}
+
+/// Fake merkle data shred: zero signature, variant, slot and index - the rest is padding.
+fn shred(slot: u64, index: u32) -> Vec<u8> {
+    let mut packet = vec![0u8; 1203];
+    packet[64] = 0x96;
+    packet[65..73].copy_from_slice(&slot.to_le_bytes());
+    packet[73..77].copy_from_slice(&index.to_le_bytes());
+    packet
+}
+
+/// Replay the chain to `target` as raw UDP shreds, like a ShredStream proxy:
+/// a burst of shreds per slot on the chain's schedule delayed by
+/// `behavior.delay`, a retransmit of the previous slot and some noise: a
+/// shred for an absurdly high slot ahead of the burst, so the very first
+/// packet is bogus, then a short packet and a full-size one without a shred
+/// variant.
+pub fn spawn_shred_replayer(chain: MockChain, behavior: StreamBehavior, target: SocketAddr) {
+    tokio::spawn(async move {
+        let socket = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+
+        for index in 0..chain.slot_count {
+            tokio::time::sleep_until((chain.scheduled_at(index) + behavior.delay).into()).await;
+
+            let slot = chain.first_slot + index;
+            let _ = socket.send_to(&shred(u64::MAX - index, 0), target).await;
+            for shred_index in 0..4 {
+                let _ = socket.send_to(&shred(slot, shred_index), target).await;
+            }
+            if index > 0 {
+                let _ = socket.send_to(&shred(slot - 1, 4), target).await;
+            }
+            let _ = socket.send_to(b"not a shred", target).await;
+            let _ = socket.send_to(&[0xffu8; 1228], target).await;
+        }
+    });
+}
+
+/// Scratch directory for one test's config and output files.
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("speedrace-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Write a config racing the given named endpoints.
+pub fn write_config(dir: &Path, max_slots: usize, warmup_slots: usize, streams: &[(&str, SocketAddr)], extra: &str) -> PathBuf {
+    let streams: Vec<_> = streams.iter().map(|(name, addr)| (*name, MockStream::Grpc(*addr))).collect();
+    write_mixed_config(dir, max_slots, warmup_slots, &streams, extra)
+}
+
+/// Endpoint of a mock stream, by the kind of stream that races it.
+pub enum MockStream {
+    Grpc(SocketAddr),
+    Websocket(SocketAddr),
+    Shreds(SocketAddr),
+    /// An NDJSON race export to replay the stream's recorded slots from.
+    Replay(PathBuf),
+}
+
+/// Like `write_config`, with streams of any kind.
+pub fn write_mixed_config(dir: &Path, max_slots: usize, warmup_slots: usize, streams: &[(&str, MockStream)], extra: &str) -> PathBuf {
+    let streams: Vec<String> = streams
+        .iter()
+        .map(|(name, stream)| match stream {
+            MockStream::Grpc(addr) => format!("    {{ name = \"{}\", endpoint = \"http://{}\" }},", name, addr),
+            MockStream::Websocket(addr) => {
+                format!("    {{ name = \"{}\", kind = \"websocket\", endpoint = \"ws://{}\" }},", name, addr)
+            }
+            MockStream::Shreds(addr) => {
+                format!("    {{ name = \"{}\", kind = \"shreds\", endpoint = \"udp://{}\" }},", name, addr)
+            }
+            MockStream::Replay(path) => {
+                format!("    {{ name = \"{}\", kind = \"replay\", endpoint = \"{}\" }},", name, path.display())
+            }
+        })
+        .collect();
+
+    let config = format!(
+        "max_slots = {}\nstop_at_max = true\nwarmup_slots = {}\n{}\nstreams = [\n{}\n]\n",
+        max_slots,
+        warmup_slots,
+        extra,
+        streams.join("\n")
+    );
+
+    let path = dir.join("config.toml");
+    std::fs::write(&path, config).unwrap();
+    path
+}
+
+/// Run the speedrace binary to completion and return the final JSON summary.
+pub async fn run_race(config: &Path, args: &[&str]) -> Value {
+    let mut child = start_race(config, args);
+
+    let status = tokio::time::timeout(Duration::from_secs(60), child.wait())
+        .await
+        .expect("race did not finish in time")
+        .unwrap();
+    assert!(status.success(), "speedrace exited with {}", status);
+
+    final_summary(config)
+}
+
+/// Run the speedrace binary, send it `signal` (e.g. "INT") after `after`,
+/// and return its exit status with the final JSON summary.
+pub async fn interrupt_race(config: &Path, args: &[&str], after: Duration, signal: &str) -> (ExitStatus, Value) {
+    let mut child = start_race(config, args);
+
+    tokio::time::sleep(after).await;
+    let pid = child.id().expect("speedrace exited before the signal").to_string();
+    let killed = std::process::Command::new("kill").arg(format!("-{}", signal)).arg(&pid).status().unwrap();
+    assert!(killed.success(), "failed to send SIG{}", signal);
+
+    let status = tokio::time::timeout(Duration::from_secs(10), child.wait())
+        .await
+        .expect("race did not shut down in time")
+        .unwrap();
+
+    (status, final_summary(config))
+}
+
+fn start_race(config: &Path, args: &[&str]) -> Child {
+    tokio::process::Command::new(env!("CARGO_BIN_EXE_gRPC_speedrace"))
+        .arg("--config")
+        .arg(config)
+        .arg("--json-output")
+        .arg(config.with_file_name("summary.ndjson"))
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("failed to start speedrace binary")
+}
+
+fn final_summary(config: &Path) -> Value {
+    let output = std::fs::read_to_string(config.with_file_name("summary.ndjson")).unwrap();
+    output
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .find(|summary| summary["final"] == Value::Bool(true))
+        .expect("no final summary written")
+}
+
+/// Metrics for one stream in the named race of a summary.
+pub fn stream_metrics<'a>(summary: &'a Value, race: &str, stream: &str) -> &'a Value {
+    summary["races"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["race"] == race)
+        .unwrap_or_else(|| panic!("no {} race in summary", race))["streams"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["name"] == stream)
+        .unwrap_or_else(|| panic!("no {} stream in summary", stream))
+}
+
+/// Stream names of the named race, fastest first.
+pub fn ranking(summary: &Value, race: &str) -> Vec<String> {
+    summary["races"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["race"] == race)
+        .unwrap()["streams"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["name"].as_str().unwrap().to_string())
+        .collect()
+}
@@ -0,0 +1,449 @@
+mod common;
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use common::{spawn_mock, spawn_ws_mock, MockChain, StreamBehavior, WS_SLOT_UPDATE_TYPES};
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use grpc_speedrace::config::GrpcTuning;
+use grpc_speedrace::referee::CloseReason;
+use grpc_speedrace::sources::{SlotSource, SourceContext};
+use grpc_speedrace::{Config, Race, RaceEnd, RaceEvent, RaceKey, RaceKind, Referee, StreamConfig, StreamKind};
+use yellowstone_grpc_proto::prelude::SlotStatus;
+
+#[tokio::test]
+async fn races_run_from_the_library_api() {
+    let chain = MockChain::new(Duration::from_secs(2), 40, Duration::from_millis(60));
+    let fast = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let slow = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    let config = Config {
+        max_slots: 20,
+        stop_at_max: true,
+        warmup_slots: 3,
+        streams: vec![
+            StreamConfig::new("Fast", StreamKind::Grpc, format!("http://{}", fast)),
+            StreamConfig::new("Slow", StreamKind::Grpc, format!("http://{}", slow)),
+        ],
+        ..Default::default()
+    };
+
+    // Subscribed before any stream connects, so nothing is missed
+    let builder = Race::builder(config).unwrap();
+    let mut events = builder.referee().subscribe_events().await;
+    let mut closed = builder.referee().subscribe().await;
+    let mut race = builder.start().await.unwrap();
+
+    let end = tokio::time::timeout(Duration::from_secs(30), race.wait()).await.expect("race did not finish in time");
+    assert_eq!(end, RaceEnd::Complete);
+
+    let summaries = race.referee().summarize().await;
+    let slot = summaries.iter().find(|s| s.race == RaceKind::Slot.to_string()).unwrap();
+    assert_eq!(slot.streams[0].name, "Fast");
+    assert_eq!(slot.streams[0].wins, 20);
+    assert_eq!(slot.streams[1].total_races, 20);
+
+    race.shutdown().await;
+
+    // Every event from the first connection attempt on, and every event stream ends on shutdown
+    let mut connecting = Vec::new();
+    let mut connected = Vec::new();
+    let mut reports = 0;
+    while let Some(event) = events.recv().await {
+        match event {
+            RaceEvent::Connecting { stream_id, .. } => connecting.push(stream_id),
+            RaceEvent::Connected { stream_id, .. } => connected.push(stream_id),
+            RaceEvent::Report { .. } => reports += 1,
+            _ => {}
+        }
+    }
+    connecting.sort();
+    connected.sort();
+    assert_eq!(connecting, ["Fast", "Slow"]);
+    assert_eq!(connected, ["Fast", "Slow"]);
+    assert!(reports >= 46, "only {} reports", reports);
+
+    let mut closed_races = 0;
+    while closed.recv().await.is_some() {
+        closed_races += 1;
+    }
+    assert!(closed_races >= 20, "only {} races closed", closed_races);
+}
+
+#[tokio::test]
+async fn websocket_slot_updates_race_as_slot_statuses() {
+    let chain = MockChain::new(Duration::from_secs(2), 40, Duration::from_millis(60));
+    let pubsub = spawn_ws_mock(chain, StreamBehavior::delayed(0)).await;
+
+    let config = Config {
+        max_slots: 10,
+        stop_at_max: true,
+        warmup_slots: 3,
+        races: vec![RaceKind::Slot, RaceKind::SlotStatus],
+        streams: vec![StreamConfig::new("PubSub", StreamKind::Websocket, format!("ws://{}", pubsub))],
+        ..Default::default()
+    };
+
+    let builder = Race::builder(config).unwrap();
+    let mut events = builder.referee().subscribe_events().await;
+    let mut race = builder.start().await.unwrap();
+    let end = tokio::time::timeout(Duration::from_secs(30), race.wait()).await.expect("race did not finish in time");
+    assert_eq!(end, RaceEnd::Complete);
+    race.shutdown().await;
+
+    let mut slots = Vec::new();
+    let mut statuses: BTreeMap<u64, Vec<SlotStatus>> = BTreeMap::new();
+    while let Some(event) = events.recv().await {
+        match event {
+            RaceEvent::Report { key: RaceKey::Slot(slot), .. } => slots.push(slot),
+            RaceEvent::Report { key: RaceKey::SlotStatus { slot, status }, created_at, .. } => {
+                assert!(created_at.is_some(), "slot {} {:?} without created_at", slot, status);
+                statuses.entry(slot).or_default().push(status);
+            }
+            _ => {}
+        }
+    }
+    assert!(slots.len() >= 10, "only {} slots", slots.len());
+
+    // Every type but the unknown one reaches the referee, as its Yellowstone status - the last
+    // slot may have been cut off by the race ending
+    assert_eq!(WS_SLOT_UPDATE_TYPES.last(), Some(&"unknownStage"));
+    let expected = [
+        SlotStatus::SlotFirstShredReceived,
+        SlotStatus::SlotCreatedBank,
+        SlotStatus::SlotCompleted,
+        SlotStatus::SlotProcessed,
+        SlotStatus::SlotConfirmed,
+        SlotStatus::SlotFinalized,
+    ];
+    statuses.pop_last();
+    assert!(statuses.len() >= 10, "only {} slots with status updates", statuses.len());
+    for (slot, reported) in &statuses {
+        assert_eq!(reported, &expected, "slot {}", slot);
+    }
+}
+
+#[tokio::test]
+async fn grpc_tuning_applies_per_stream() {
+    let chain = MockChain::new(Duration::from_secs(2), 40, Duration::from_millis(60));
+    let tuned = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let plain = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    let mut tuned_stream = StreamConfig::new("Tuned", StreamKind::Grpc, format!("http://{}", tuned));
+    tuned_stream.grpc = GrpcTuning {
+        connect_timeout_ms: Some(2000),
+        timeout_ms: Some(5000),
+        http2_keep_alive_interval_ms: Some(1000),
+        keep_alive_timeout_ms: Some(500),
+        keep_alive_while_idle: Some(true),
+        http2_adaptive_window: Some(false),
+        initial_stream_window_size: Some(4 * 1024 * 1024),
+        initial_connection_window_size: Some(8 * 1024 * 1024),
+        tcp_nodelay: Some(true),
+        tcp_keepalive_ms: Some(15000),
+        max_decoding_message_size: Some(64 * 1024 * 1024),
+    };
+
+    let config = Config {
+        max_slots: 10,
+        stop_at_max: true,
+        warmup_slots: 3,
+        streams: vec![tuned_stream, StreamConfig::new("Plain", StreamKind::Grpc, format!("http://{}", plain))],
+        ..Default::default()
+    };
+
+    let mut race = Race::start(config).await.unwrap();
+    let end = tokio::time::timeout(Duration::from_secs(30), race.wait()).await.expect("race did not finish in time");
+    assert_eq!(end, RaceEnd::Complete);
+
+    let summaries = race.referee().summarize().await;
+    let slot = summaries.iter().find(|s| s.race == RaceKind::Slot.to_string()).unwrap();
+    assert_eq!(slot.streams[0].name, "Tuned");
+    assert_eq!(slot.streams[0].wins, 10);
+    race.shutdown().await;
+
+    // A decoding limit below the size of a slot update only holds back the stream it is set on
+    let mut capped_stream = StreamConfig::new("Capped", StreamKind::Grpc, format!("http://{}", tuned));
+    capped_stream.grpc.max_decoding_message_size = Some(8);
+    let config = Config {
+        max_slots: 5,
+        stop_at_max: true,
+        warmup_slots: 3,
+        race_deadline_ms: 200,
+        streams: vec![capped_stream, StreamConfig::new("Plain", StreamKind::Grpc, format!("http://{}", plain))],
+        ..Default::default()
+    };
+
+    let builder = Race::builder(config).unwrap();
+    let mut events = builder.referee().subscribe_events().await;
+    let mut race = builder.start().await.unwrap();
+    let end = tokio::time::timeout(Duration::from_secs(30), race.wait()).await.expect("race did not finish in time");
+    assert_eq!(end, RaceEnd::Complete);
+    race.shutdown().await;
+
+    let mut capped_reports = 0;
+    let mut capped_errors = Vec::new();
+    while let Some(event) = events.recv().await {
+        match event {
+            RaceEvent::Report { stream_id, .. } if stream_id == "Capped" => capped_reports += 1,
+            RaceEvent::StreamError { stream_id, error, .. } if stream_id == "Capped" => capped_errors.push(error),
+            _ => {}
+        }
+    }
+    assert_eq!(capped_reports, 0);
+    // Decoding an oversized message fails with OutOfRange - the stream error is counted by that code
+    assert!(!capped_errors.is_empty() && capped_errors.iter().all(|e| e == "gRPC OutOfRange"), "{:?}", capped_errors);
+
+    // Out of range settings are rejected before anything connects
+    let config = Config::from_toml(r#"
+        streams = [
+            { name = "A", endpoint = "http://127.0.0.1:1", grpc = { initial_stream_window_size = 4294967295 } },
+        ]
+    "#).unwrap();
+    let error = config.validate().unwrap_err().to_string();
+    assert!(error.contains("initial_stream_window_size"), "{}", error);
+
+    let config = Config::from_toml(r#"
+        streams = [
+            { name = "A", endpoint = "http://127.0.0.1:1", grpc = { keep_alive_timeout_ms = 500 } },
+        ]
+    "#).unwrap();
+    let error = config.validate().unwrap_err().to_string();
+    assert!(error.contains("http2_keep_alive_interval_ms"), "{}", error);
+}
+
+#[test]
+fn reconnect_policy_rejects_non_finite_floats() {
+    for setting in ["multiplier = nan", "multiplier = inf", "jitter = nan"] {
+        let config = Config::from_toml(&format!(r#"
+            streams = [
+                {{ name = "A", endpoint = "http://127.0.0.1:1", reconnect = {{ {} }} }},
+            ]
+        "#, setting)).unwrap();
+        let error = config.validate().unwrap_err().to_string();
+        assert!(error.contains(setting.split(' ').next().unwrap()), "{}: {}", setting, error);
+    }
+}
+
+// Reports slots 1000.. on a schedule shared by every instance, `delay` after each slot starts
+struct ScheduledSource {
+    context: SourceContext,
+    start: Instant,
+    delay: Duration,
+}
+
+impl SlotSource for ScheduledSource {
+    fn connect(&mut self, _resume_from: Option<u64>) -> BoxFuture<'_, anyhow::Result<()>> {
+        async { Ok(()) }.boxed()
+    }
+
+    fn run(&mut self) -> BoxFuture<'_, anyhow::Result<()>> {
+        async move {
+            for i in 0..30 {
+                tokio::time::sleep_until((self.start + Duration::from_millis(40) * i + self.delay).into()).await;
+                self.context.report(RaceKey::Slot(1000 + i as u64), self.context.now(), None);
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[tokio::test]
+async fn custom_sources_race_like_built_in_ones() {
+    let config = Config {
+        max_slots: 10,
+        stop_at_max: true,
+        warmup_slots: 3,
+        streams: vec![
+            StreamConfig::new("Early", StreamKind::Custom, "in-process"),
+            StreamConfig::new("Late", StreamKind::Custom, "in-process"),
+        ],
+        ..Default::default()
+    };
+
+    let start = Instant::now() + Duration::from_millis(100);
+    let mut race = Race::builder(config.clone())
+        .unwrap()
+        .source("Early", move |context| ScheduledSource { context, start, delay: Duration::ZERO })
+        .source("Late", move |context| ScheduledSource { context, start, delay: Duration::from_millis(15) })
+        .start()
+        .await
+        .unwrap();
+    let end = tokio::time::timeout(Duration::from_secs(30), race.wait()).await.expect("race did not finish in time");
+    assert_eq!(end, RaceEnd::Complete);
+
+    let summaries = race.referee().summarize().await;
+    let slot = summaries.iter().find(|s| s.race == RaceKind::Slot.to_string()).unwrap();
+    assert_eq!(slot.streams[0].name, "Early");
+    assert_eq!(slot.streams[0].wins, 10);
+    assert_eq!(slot.streams[1].total_races, 10);
+    race.shutdown().await;
+
+    // Every custom stream needs a source, and only custom streams take one
+    let error = Race::builder(config.clone()).unwrap().start().await.err().unwrap().to_string();
+    assert!(error.contains("No source registered for custom stream"), "{}", error);
+
+    let error = Race::builder(config.clone())
+        .unwrap()
+        .source("Early", move |context| ScheduledSource { context, start, delay: Duration::ZERO })
+        .source("Late", move |context| ScheduledSource { context, start, delay: Duration::ZERO })
+        .source("Missing", move |context| ScheduledSource { context, start, delay: Duration::ZERO })
+        .start()
+        .await
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("unknown stream 'Missing'"), "{}", error);
+
+    let mut config = config;
+    config.streams[1].kind = StreamKind::Grpc;
+    let error = Race::builder(config)
+        .unwrap()
+        .source("Early", move |context| ScheduledSource { context, start, delay: Duration::ZERO })
+        .source("Late", move |context| ScheduledSource { context, start, delay: Duration::ZERO })
+        .start()
+        .await
+        .err()
+        .unwrap()
+        .to_string();
+    assert!(error.contains("only custom streams"), "{}", error);
+}
+
+fn report(key: RaceKey, stream: &str, at_ms: u128) -> RaceEvent {
+    RaceEvent::Report { key, stream_id: stream.to_string(), timestamp: at_ms * 1_000_000, created_at: None }
+}
+
+fn transaction(index: u64) -> RaceKey {
+    RaceKey::Transaction { signature: format!("sig{}", index), slot: 100 }
+}
+
+#[tokio::test]
+async fn busy_keyed_races_stay_open_until_they_close() {
+    // A rolling window far smaller than the transactions landing within one race deadline
+    let config = Config {
+        max_slots: 2,
+        stop_at_max: false,
+        warmup_slots: 0,
+        races: vec![RaceKind::Transaction],
+        race_deadline_ms: 100,
+        ..Default::default()
+    };
+    let (referee, _events) = Referee::new(&config, None);
+
+    for index in 0..6 {
+        referee.process_event(report(transaction(index), "Fast", index as u128)).await;
+    }
+    for index in 0..6 {
+        referee.process_event(report(transaction(index), "Slow", 10 + index as u128)).await;
+    }
+
+    let summaries = referee.summarize().await;
+    let fast = summaries[0].streams.iter().find(|s| s.name == "Fast").unwrap();
+    let slow = summaries[0].streams.iter().find(|s| s.name == "Slow").unwrap();
+    assert_eq!(fast.wins, 6);
+    assert_eq!(slow.wins, 0);
+    assert_eq!(slow.missed, 0);
+
+    // Once closed, the oldest races leave the window - a straggler for one doesn't start it again
+    for index in 6..10 {
+        referee.process_event(report(transaction(index), "Fast", 20 + index as u128)).await;
+    }
+    referee.process_event(report(transaction(1), "Other", 40)).await;
+
+    let summaries = referee.summarize().await;
+    assert!(!summaries[0].streams.iter().any(|s| s.name == "Other"));
+    assert_eq!(summaries[0].total_tracked, 4);
+}
+
+#[tokio::test]
+async fn races_wait_for_every_configured_stream() {
+    let config = Config {
+        warmup_slots: 0,
+        streams: ["Fast", "Slow", "Late"]
+            .into_iter()
+            .map(|name| StreamConfig::new(name, StreamKind::Grpc, "http://127.0.0.1:1"))
+            .collect(),
+        ..Default::default()
+    };
+    let (referee, _events) = Referee::new(&config, None);
+    let mut closed = referee.subscribe().await;
+
+    // A stream that connects after the others is still on time for a race they already finished
+    referee.process_event(report(RaceKey::Slot(100), "Fast", 0)).await;
+    referee.process_event(report(RaceKey::Slot(100), "Slow", 5)).await;
+    assert!(closed.try_recv().is_err(), "race closed before every configured stream reported");
+    referee.process_event(report(RaceKey::Slot(100), "Late", 50)).await;
+    assert_eq!(closed.try_recv().unwrap().reason, CloseReason::Complete);
+
+    let summaries = referee.summarize().await;
+    assert_eq!(summaries[0].stream_count, 3);
+    assert_eq!(summaries[0].completed_races, 1);
+    let late = summaries[0].streams.iter().find(|s| s.name == "Late").unwrap();
+    assert_eq!(late.total_races, 1);
+    assert_eq!(late.late, 0);
+}
+
+#[tokio::test]
+async fn timeout_scoring_never_improves_slow_reports() {
+    let config = Config {
+        warmup_slots: 0,
+        race_deadline_ms: 10_000,
+        streams: ["Fast", "Slow"]
+            .into_iter()
+            .map(|name| StreamConfig::new(name, StreamKind::Grpc, "http://127.0.0.1:1"))
+            .collect(),
+        ..Default::default()
+    };
+    let (referee, _events) = Referee::new(&config, None);
+
+    // On time, but further behind than the 1000ms scoring timeout
+    referee.process_event(report(RaceKey::Slot(100), "Fast", 0)).await;
+    referee.process_event(report(RaceKey::Slot(100), "Slow", 3000)).await;
+
+    // Past the race deadline, 15s behind
+    referee.process_event(report(RaceKey::Slot(101), "Fast", 10_000)).await;
+    referee.process_event(RaceEvent::Tick { timestamp: 21_000 * 1_000_000 }).await;
+    referee.process_event(report(RaceKey::Slot(101), "Slow", 25_000)).await;
+
+    let summaries = referee.summarize().await;
+    let slow = summaries[0].streams.iter().find(|s| s.name == "Slow").unwrap();
+    assert_eq!(slow.late, 1);
+    assert_eq!(slow.penalized, 2);
+    assert!(slow.median_time_behind_ms >= 3000.0, "{}", slow.median_time_behind_ms);
+    assert_eq!(slow.p99_time_behind_ms, 15_000.0);
+}
+
+#[tokio::test]
+async fn watchdog_ignores_quiet_sparse_races() {
+    let config = Config {
+        warmup_slots: 0,
+        races: vec![RaceKind::Slot, RaceKind::Account],
+        streams: ["Busy", "Quiet"]
+            .into_iter()
+            .map(|name| StreamConfig::new(name, StreamKind::Grpc, "http://127.0.0.1:1"))
+            .collect(),
+        ..Default::default()
+    };
+    let (referee, _events) = Referee::new(&config, None);
+
+    for stream in ["Busy", "Quiet"] {
+        referee.process_event(RaceEvent::Connected { stream_id: stream.to_string(), timestamp: 0, resumed_from: None }).await;
+        for slot in 100..110 {
+            referee.process_event(report(RaceKey::Slot(slot), stream, slot as u128)).await;
+        }
+    }
+
+    // Only Busy sees an update on a later slot of a watched account - Quiet is not behind on slots
+    let update = RaceKey::Account { pubkey: "pool".to_string(), slot: 112, write_version: 1 };
+    referee.process_event(report(update, "Busy", 200)).await;
+    assert_eq!(referee.check_stale("Quiet", 1_000_000_000).await, None);
+    assert_eq!(referee.check_stale("Quiet", 20_000_000_000).await, None);
+
+    // Falling behind on the slot race still counts
+    for slot in 110..200 {
+        referee.process_event(report(RaceKey::Slot(slot), "Busy", 20_000 + slot as u128)).await;
+    }
+    assert_eq!(referee.check_stale("Quiet", 20_500_000_000).await, Some("too far behind the leader"));
+}
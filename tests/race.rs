@@ -0,0 +1,575 @@
+mod common;
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use common::{
+    free_udp_addr, interrupt_race, mock_signature, ranking, run_race, spawn_mock, spawn_shred_replayer, spawn_ws_mock, stream_metrics, test_dir,
+    write_config, write_mixed_config, MockChain, MockStream, StreamBehavior,
+};
+
+const SLOT_INTERVAL: Duration = Duration::from_millis(60);
+
+fn chain(slot_count: u64) -> MockChain {
+    MockChain::new(Duration::from_secs(2), slot_count, SLOT_INTERVAL)
+}
+
+fn median(metrics: &serde_json::Value) -> f64 {
+    metrics["median_time_behind_ms"].as_f64().unwrap()
+}
+
+#[tokio::test]
+async fn fastest_stream_wins_and_latencies_match_delays() {
+    let chain = chain(40);
+    let fast = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let medium = spawn_mock(chain, StreamBehavior::delayed(15)).await;
+    let slow = spawn_mock(chain, StreamBehavior::delayed(40)).await;
+
+    let dir = test_dir("delays");
+    let config = write_config(&dir, 20, 3, &[("Fast", fast), ("Medium", medium), ("Slow", slow)], "");
+    let summary = run_race(&config, &[]).await;
+
+    assert_eq!(ranking(&summary, "slot"), vec!["Fast", "Medium", "Slow"]);
+
+    let fast = stream_metrics(&summary, "slot", "Fast");
+    assert_eq!(fast["wins"], 20);
+    assert_eq!(fast["total_races"], 20);
+    assert_eq!(median(fast), 0.0);
+
+    let medium = stream_metrics(&summary, "slot", "Medium");
+    assert_eq!(medium["wins"], 0);
+    assert!((10.0..25.0).contains(&median(medium)), "medium median {}", median(medium));
+
+    let slow = stream_metrics(&summary, "slot", "Slow");
+    assert!((35.0..55.0).contains(&median(slow)), "slow median {}", median(slow));
+    assert!(slow["p99_time_behind_ms"].as_f64().unwrap() >= median(slow));
+}
+
+#[tokio::test]
+async fn dropped_slots_are_won_by_the_next_stream() {
+    let chain = chain(40);
+    let lossy = spawn_mock(chain, StreamBehavior {
+        drops: (0..40).filter(|i| i % 5 == 0).collect(),
+        ..StreamBehavior::delayed(0)
+    }).await;
+    let steady = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    let dir = test_dir("drops");
+    let config = write_config(&dir, 20, 3, &[("Lossy", lossy), ("Steady", steady)], "");
+    let summary = run_race(&config, &[]).await;
+
+    let lossy = stream_metrics(&summary, "slot", "Lossy");
+    let steady = stream_metrics(&summary, "slot", "Steady");
+    assert_eq!(steady["total_races"], 20);
+    assert_eq!(lossy["total_races"], 16);
+    assert_eq!(lossy["wins"], 16);
+    assert_eq!(steady["wins"], 4);
+    assert_eq!(lossy["missed"], 4);
+    assert_eq!(lossy["missed_rate"], 20.0);
+    assert_eq!(steady["missed"], 0);
+}
+
+#[tokio::test]
+async fn missed_slots_are_penalized_in_rankings() {
+    // Lossy is fastest when it reports, but drops most slots
+    async fn race(scoring: &[&str]) -> serde_json::Value {
+        let chain = chain(40);
+        let lossy = spawn_mock(chain, StreamBehavior {
+            drops: (0..40).filter(|i| i % 5 != 0).collect(),
+            ..StreamBehavior::delayed(0)
+        }).await;
+        let steady = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+        let dir = test_dir(&format!("penalties-{}", scoring[1]));
+        let config = write_config(&dir, 20, 3, &[("Lossy", lossy), ("Steady", steady)], "");
+        run_race(&config, scoring).await
+    }
+
+    let summary = race(&["--scoring", "reported"]).await;
+    assert_eq!(ranking(&summary, "slot"), vec!["Lossy", "Steady"]);
+    assert_eq!(stream_metrics(&summary, "slot", "Lossy")["penalized"], 0);
+
+    let summary = race(&["--scoring", "timeout", "--scoring-timeout-ms", "500"]).await;
+    assert_eq!(ranking(&summary, "slot"), vec!["Steady", "Lossy"]);
+    let lossy = stream_metrics(&summary, "slot", "Lossy");
+    assert_eq!(lossy["penalized"], lossy["missed"]);
+    assert_eq!(median(lossy), 500.0);
+    assert_eq!(stream_metrics(&summary, "slot", "Steady")["penalized"], 0);
+}
+
+#[tokio::test]
+async fn dropping_slow_slots_never_beats_reporting_them() {
+    // Honest reports most slots 300ms late, Dropper drops those same slots
+    let chain = chain(40);
+    let slow: HashSet<u64> = (0..40).filter(|i| i % 5 != 0).collect();
+    let fast = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let honest = spawn_mock(chain, StreamBehavior {
+        slow: slow.clone(),
+        slow_delay: Duration::from_millis(300),
+        ..StreamBehavior::delayed(20)
+    }).await;
+    let dropper = spawn_mock(chain, StreamBehavior {
+        drops: slow,
+        ..StreamBehavior::delayed(20)
+    }).await;
+
+    let dir = test_dir("dropping");
+    let config = write_config(&dir, 20, 3, &[("Fast", fast), ("Honest", honest), ("Dropper", dropper)], "");
+    let args = ["--scoring", "timeout", "--scoring-timeout-ms", "100", "--race-deadline-ms", "400"];
+    let summary = run_race(&config, &args).await;
+
+    assert_eq!(ranking(&summary, "slot"), vec!["Fast", "Honest", "Dropper"]);
+    let honest = stream_metrics(&summary, "slot", "Honest");
+    let dropper = stream_metrics(&summary, "slot", "Dropper");
+    assert!(median(dropper) > median(honest), "dropper {} honest {}", median(dropper), median(honest));
+}
+
+#[tokio::test]
+async fn stream_that_never_reports_misses_every_race() {
+    async fn race(scoring: &[&str]) -> serde_json::Value {
+        let chain = chain(40);
+        let steady = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+        let silent = spawn_mock(chain, StreamBehavior {
+            silent: true,
+            ..Default::default()
+        }).await;
+
+        let dir = test_dir(&format!("silent-{}", scoring[1]));
+        let config = write_config(&dir, 20, 3, &[("Silent", silent), ("Steady", steady)], "");
+        run_race(&config, scoring).await
+    }
+
+    let summary = race(&["--scoring", "reported"]).await;
+    assert_eq!(ranking(&summary, "slot"), vec!["Steady", "Silent"]);
+    let silent = stream_metrics(&summary, "slot", "Silent");
+    assert_eq!(silent["total_races"], 0);
+    assert_eq!(silent["missed"], 20);
+    assert_eq!(silent["missed_rate"], 100.0);
+    assert_eq!(silent["penalized"], 0);
+
+    let summary = race(&["--scoring", "timeout", "--scoring-timeout-ms", "500"]).await;
+    assert_eq!(ranking(&summary, "slot"), vec!["Steady", "Silent"]);
+    let silent = stream_metrics(&summary, "slot", "Silent");
+    assert_eq!(silent["missed"], 20);
+    assert_eq!(silent["penalized"], 20);
+    assert_eq!(median(silent), 500.0);
+}
+
+#[tokio::test]
+async fn reports_after_the_race_deadline_are_late() {
+    let chain = chain(40);
+    let fast = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let slow = spawn_mock(chain, StreamBehavior::delayed(150)).await;
+
+    let dir = test_dir("deadline");
+    let config = write_config(&dir, 20, 3, &[("Fast", fast), ("Slow", slow)], "");
+    let summary = run_race(&config, &["--race-deadline-ms", "100"]).await;
+
+    assert_eq!(ranking(&summary, "slot"), vec!["Fast", "Slow"]);
+    assert_eq!(stream_metrics(&summary, "slot", "Fast")["late"], 0);
+
+    // Races still open when the race ends are closed without Slow, so count as missed
+    let slow = stream_metrics(&summary, "slot", "Slow");
+    assert_eq!(slow["total_races"], 0);
+    let late = slow["late"].as_u64().unwrap();
+    assert!(late >= 15, "late {}", late);
+    assert_eq!(late + slow["missed"].as_u64().unwrap(), 20);
+    assert_eq!(slow["penalized"], 20);
+    assert_eq!(median(slow), 1000.0);
+}
+
+#[tokio::test]
+async fn transactions_race_per_signature_and_accounts_per_write_version() {
+    let chain = chain(40);
+    let fast = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let slow = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    let dir = test_dir("keyed");
+    let extra = "races = [\"transaction\", \"account\"]\naccounts = { account = [\"58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2\"] }";
+    let config = write_config(&dir, 20, 3, &[("Fast", fast), ("Slow", slow)], extra);
+    let export = dir.join("races.ndjson");
+    let summary = run_race(&config, &["--export", export.to_str().unwrap(), "--export-format", "ndjson"]).await;
+
+    // Every slot carries two transactions and two writes to the same account - each is its own race
+    for race in ["transaction", "account"] {
+        assert_eq!(ranking(&summary, race), vec!["Fast", "Slow"], "{} ranking", race);
+        let fast = stream_metrics(&summary, race, "Fast");
+        assert_eq!(fast["wins"], 20, "{} wins", race);
+        assert_eq!(fast["duplicates"], 0, "{} duplicates", race);
+        let slow = stream_metrics(&summary, race, "Slow");
+        assert_eq!(slow["total_races"], 20, "{} races", race);
+        assert!((15.0..30.0).contains(&median(slow)), "{} slow median {}", race, median(slow));
+    }
+
+    let records: Vec<serde_json::Value> = std::fs::read_to_string(&export).unwrap()
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    let signatures: HashSet<&str> = records.iter()
+        .filter(|r| r["race"] == "transaction")
+        .map(|r| r["key"].as_str().unwrap())
+        .collect();
+    assert_eq!(signatures.len(), 20);
+    let first_slot = chain.first_slot + 3;
+    assert!(signatures.iter().any(|key| key.contains(&mock_signature(first_slot, 1))), "keys {:?}", signatures);
+}
+
+#[tokio::test]
+async fn blocks_on_different_forks_race_separately() {
+    let chain = chain(40);
+    let canonical = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let forked = spawn_mock(chain, StreamBehavior {
+        fork_at: Some(10),
+        ..StreamBehavior::delayed(20)
+    }).await;
+
+    let dir = test_dir("blocks");
+    let config = write_config(&dir, 20, 3, &[("Canonical", canonical), ("Forked", forked)], "races = [\"block\", \"block_meta\"]");
+    let summary = run_race(&config, &[]).await;
+
+    // The same slot under two blockhashes is two races, each missed by the other stream
+    for race in ["block", "block_meta"] {
+        assert_eq!(ranking(&summary, race), vec!["Canonical", "Forked"], "{} ranking", race);
+        let canonical = stream_metrics(&summary, race, "Canonical");
+        let forked = stream_metrics(&summary, race, "Forked");
+        assert_eq!(canonical["missed"], 1, "{} canonical missed", race);
+        assert_eq!(forked["missed"], 1, "{} forked missed", race);
+        assert_eq!(forked["wins"], 1, "{} forked wins", race);
+        assert_eq!(canonical["wins"], canonical["total_races"], "{} canonical wins", race);
+    }
+}
+
+#[tokio::test]
+async fn slot_stages_race_separately() {
+    let chain = chain(40);
+    // Quick to process, slow to confirm - and the other way round
+    let processes_first = spawn_mock(chain, StreamBehavior {
+        confirm_delay: Duration::from_millis(30),
+        ..StreamBehavior::delayed(0)
+    }).await;
+    let confirms_first = spawn_mock(chain, StreamBehavior::delayed(15)).await;
+
+    let dir = test_dir("slot-stages");
+    let streams = [("ProcessesFirst", processes_first), ("ConfirmsFirst", confirms_first)];
+    let config = write_config(&dir, 20, 3, &streams, "races = [\"slot_status\"]");
+    let summary = run_race(&config, &[]).await;
+
+    assert_eq!(ranking(&summary, "slot_status/processed"), vec!["ProcessesFirst", "ConfirmsFirst"]);
+    assert_eq!(ranking(&summary, "slot_status/confirmed"), vec!["ConfirmsFirst", "ProcessesFirst"]);
+    for stage in ["slot_status/processed", "slot_status/confirmed"] {
+        let leader = &ranking(&summary, stage)[0];
+        let leader = stream_metrics(&summary, stage, leader);
+        assert_eq!(leader["wins"], 10, "{} wins", stage);
+        assert_eq!(leader["duplicates"], 0, "{} duplicates", stage);
+    }
+}
+
+#[tokio::test]
+async fn duplicates_and_reorders_keep_the_ranking() {
+    let chain = chain(40);
+    // Keep anomalies clear of the last raced slots so the race end doesn't cut them off
+    let every_other: HashSet<u64> = (0..16).filter(|i| i % 2 == 0).collect();
+    let duplicating = spawn_mock(chain, StreamBehavior {
+        duplicates: every_other.clone(),
+        ..StreamBehavior::delayed(0)
+    }).await;
+    let reordering = spawn_mock(chain, StreamBehavior {
+        reorders: every_other,
+        ..StreamBehavior::delayed(25)
+    }).await;
+
+    let dir = test_dir("anomalies");
+    let config = write_config(&dir, 20, 3, &[("Duplicating", duplicating), ("Reordering", reordering)], "");
+    let summary = run_race(&config, &[]).await;
+
+    assert_eq!(ranking(&summary, "slot"), vec!["Duplicating", "Reordering"]);
+    assert_eq!(stream_metrics(&summary, "slot", "Duplicating")["wins"], 20);
+    assert_eq!(stream_metrics(&summary, "slot", "Reordering")["total_races"], 20);
+
+    // Indices 4..=14 fall inside the race, the earlier ones during warmup
+    let duplicating = stream_metrics(&summary, "slot", "Duplicating");
+    assert_eq!(duplicating["duplicates"], 6);
+    assert_eq!(duplicating["reorders"], 0);
+    let reordering = stream_metrics(&summary, "slot", "Reordering");
+    assert_eq!(reordering["duplicates"], 0);
+    assert_eq!(reordering["reorders"], 6);
+    assert_eq!(reordering["max_reorder_depth"], 1);
+}
+
+#[tokio::test]
+async fn disconnected_stream_reconnects_and_keeps_racing() {
+    let chain = chain(60);
+    let flaky = spawn_mock(chain, StreamBehavior {
+        disconnect_after: Some(8),
+        ..StreamBehavior::delayed(0)
+    }).await;
+    let steady = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    let dir = test_dir("disconnect");
+    let config = write_config(&dir, 40, 3, &[("Flaky", flaky), ("Steady", steady)], "");
+    let summary = run_race(&config, &[]).await;
+
+    let flaky = stream_metrics(&summary, "slot", "Flaky");
+    let steady = stream_metrics(&summary, "slot", "Steady");
+    assert_eq!(steady["total_races"], 40);
+
+    // Slots produced while reconnecting are lost, but the stream comes back
+    let flaky_races = flaky["total_races"].as_u64().unwrap();
+    assert!(flaky_races < 40, "flaky stream reported {} races", flaky_races);
+    assert!(flaky_races > 8, "flaky stream never came back ({} races)", flaky_races);
+
+    let connections = summary["connections"].as_array().unwrap();
+    let flaky = connections.iter().find(|c| c["name"] == "Flaky").unwrap();
+    assert_eq!(flaky["connects"], 2);
+    assert_eq!(flaky["disconnects"], 1);
+    assert!(flaky["disconnected_ms"].as_f64().unwrap() > 0.0);
+    let uptime = flaky["uptime_pct"].as_f64().unwrap();
+    assert!((50.0..100.0).contains(&uptime), "flaky uptime {}", uptime);
+    let reasons = flaky["disconnect_reasons"].as_object().unwrap();
+    assert_eq!(reasons.get("gRPC Unavailable"), Some(&serde_json::json!(1)), "reasons {:?}", reasons);
+
+    let steady = connections.iter().find(|c| c["name"] == "Steady").unwrap();
+    assert_eq!(steady["disconnects"], 0);
+    // Only the initial connection handshake counts against it
+    assert!(steady["uptime_pct"].as_f64().unwrap() > 90.0);
+}
+
+#[tokio::test]
+async fn resumed_stream_replays_the_outage_without_scoring_it() {
+    let chain = chain(60);
+    let flaky = spawn_mock(chain, StreamBehavior {
+        disconnect_after: Some(8),
+        ..StreamBehavior::delayed(0)
+    }).await;
+    let steady = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    let dir = test_dir("resume");
+    let config = write_config(&dir, 40, 3, &[("Flaky", flaky), ("Steady", steady)], "resume_from_last_slot = true");
+    let summary = run_race(&config, &[]).await;
+
+    // Slots produced during the outage arrive as replays: delivered, but not raced
+    let flaky = stream_metrics(&summary, "slot", "Flaky");
+    let replayed = flaky["replayed"].as_u64().unwrap();
+    assert!(replayed > 0, "nothing replayed");
+    assert_eq!(flaky["missed"], 0);
+    assert_eq!(flaky["duplicates"], 0);
+    assert_eq!(flaky["total_races"].as_u64().unwrap() + replayed, 40);
+    assert_eq!(stream_metrics(&summary, "slot", "Steady")["total_races"], 40);
+
+    let race = summary["races"].as_array().unwrap().iter().find(|r| r["race"] == "slot").unwrap();
+    assert_eq!(race["completed_races"], 40);
+}
+
+#[tokio::test]
+async fn rejected_resume_falls_back_to_a_fresh_subscription() {
+    let chain = chain(60);
+    let flaky = spawn_mock(chain, StreamBehavior {
+        disconnect_after: Some(8),
+        reject_from_slot: true,
+        ..StreamBehavior::delayed(0)
+    }).await;
+    let steady = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    let dir = test_dir("resume-rejected");
+    let extra = "resume_from_last_slot = true\nreconnect = { initial_interval_ms = 20, jitter = 0.0 }";
+    let config = write_config(&dir, 40, 3, &[("Flaky", flaky), ("Steady", steady)], extra);
+    let summary = run_race(&config, &[]).await;
+
+    // One resumed attempt is turned down, then the stream subscribes without from_slot and keeps racing
+    let connections = summary["connections"].as_array().unwrap();
+    let flaky_connection = connections.iter().find(|c| c["name"] == "Flaky").unwrap();
+    assert_eq!(flaky_connection["connects"], 3);
+    let reasons = flaky_connection["disconnect_reasons"].as_object().unwrap();
+    assert_eq!(reasons.get("Resume rejected"), Some(&serde_json::json!(1)), "reasons {:?}", reasons);
+    assert_eq!(reasons.get("gRPC Unavailable"), Some(&serde_json::json!(1)), "reasons {:?}", reasons);
+
+    let flaky = stream_metrics(&summary, "slot", "Flaky");
+    assert_eq!(flaky["replayed"], 0);
+    let flaky_races = flaky["total_races"].as_u64().unwrap();
+    assert!(flaky_races > 20, "flaky stream never came back ({} races)", flaky_races);
+}
+
+#[tokio::test]
+async fn reconnect_policy_controls_the_retry_delay() {
+    let chain = chain(60);
+    let flaky = spawn_mock(chain, StreamBehavior {
+        disconnect_after: Some(8),
+        ..StreamBehavior::delayed(0)
+    }).await;
+    let steady = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    // The default policy waits ~500ms; a quick retry loses at most a slot or two
+    let dir = test_dir("reconnect-policy");
+    let extra = "reconnect = { initial_interval_ms = 20, jitter = 0.0 }";
+    let config = write_config(&dir, 40, 3, &[("Flaky", flaky), ("Steady", steady)], extra);
+    let summary = run_race(&config, &[]).await;
+
+    let flaky_races = stream_metrics(&summary, "slot", "Flaky")["total_races"].as_u64().unwrap();
+    assert!(flaky_races >= 37, "flaky stream reported {} races", flaky_races);
+}
+
+#[tokio::test]
+async fn watchdog_reconnects_a_stalled_stream() {
+    let chain = chain(60);
+    let stalling = spawn_mock(chain, StreamBehavior {
+        stall_after: Some(8),
+        ..StreamBehavior::delayed(0)
+    }).await;
+    let steady = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    let dir = test_dir("watchdog");
+    let extra = "watchdog = { max_silence_ms = 300 }\nreconnect = { initial_interval_ms = 20, jitter = 0.0 }";
+    let config = write_config(&dir, 40, 3, &[("Stalling", stalling), ("Steady", steady)], extra);
+    let summary = run_race(&config, &[]).await;
+
+    let connections = summary["connections"].as_array().unwrap();
+    let stalling = connections.iter().find(|c| c["name"] == "Stalling").unwrap();
+    assert_eq!(stalling["stalls"], 1);
+    assert_eq!(stalling["connects"], 2);
+    let reasons = stalling["disconnect_reasons"].as_object().unwrap();
+    assert_eq!(reasons.get("Stream stalled: silent while behind the leader"), Some(&serde_json::json!(1)), "reasons {:?}", reasons);
+    let steady = connections.iter().find(|c| c["name"] == "Steady").unwrap();
+    assert_eq!(steady["stalls"], 0);
+
+    let stalling_races = stream_metrics(&summary, "slot", "Stalling")["total_races"].as_u64().unwrap();
+    assert!(stalling_races > 8, "stalled stream never came back ({} races)", stalling_races);
+}
+
+#[tokio::test]
+async fn created_at_separates_transport_delay_from_provider_delay() {
+    let chain = chain(40);
+    let far_away = spawn_mock(chain, StreamBehavior {
+        transport_delay: Duration::from_millis(30),
+        ..Default::default()
+    }).await;
+    let slow_provider = spawn_mock(chain, StreamBehavior::delayed(30)).await;
+
+    let dir = test_dir("created-at");
+    let config = write_config(&dir, 20, 3, &[("FarAway", far_away), ("SlowProvider", slow_provider)], "");
+    let summary = run_race(&config, &[]).await;
+
+    let far_away = stream_metrics(&summary, "slot", "FarAway");
+    let transport = far_away["median_transport_delay_ms"].as_f64().unwrap();
+    let provider = far_away["median_provider_delay_ms"].as_f64().unwrap();
+    assert!((25.0..45.0).contains(&transport), "far away transport delay {}", transport);
+    assert!(provider < 5.0, "far away provider delay {}", provider);
+
+    let slow_provider = stream_metrics(&summary, "slot", "SlowProvider");
+    let transport = slow_provider["median_transport_delay_ms"].as_f64().unwrap();
+    let provider = slow_provider["median_provider_delay_ms"].as_f64().unwrap();
+    assert!(transport < 5.0, "slow provider transport delay {}", transport);
+    assert!((25.0..45.0).contains(&provider), "slow provider provider delay {}", provider);
+}
+
+#[tokio::test]
+async fn client_pings_measure_round_trip_time() {
+    let chain = chain(40);
+    let pinged = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let other = spawn_mock(chain, StreamBehavior::delayed(10)).await;
+
+    let dir = test_dir("ping-rtt");
+    let config = write_config(&dir, 20, 3, &[("Pinged", pinged), ("Other", other)], "ping_interval_ms = 100");
+    let summary = run_race(&config, &[]).await;
+
+    let pinged = stream_metrics(&summary, "slot", "Pinged");
+    let min = pinged["min_rtt_ms"].as_f64().unwrap();
+    let median = pinged["median_rtt_ms"].as_f64().unwrap();
+    let p99 = pinged["p99_rtt_ms"].as_f64().unwrap();
+    assert!(min <= median && median <= p99, "rtt min {} median {} p99 {}", min, median, p99);
+    // The mock answers on loopback, so round trips stay far below a slot
+    assert!(p99 < 50.0, "rtt p99 {}", p99);
+}
+
+#[tokio::test]
+async fn interrupted_race_writes_a_final_summary_and_export() {
+    let chain = chain(200);
+    let fast = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let slow = spawn_mock(chain, StreamBehavior::delayed(20)).await;
+
+    // Far more slots than the chain produces, so only the signal ends the run
+    let dir = test_dir("interrupt");
+    let config = write_config(&dir, 1000, 3, &[("Fast", fast), ("Slow", slow)], "");
+    let export = dir.join("races.csv");
+    let args = ["--export", export.to_str().unwrap()];
+    let (status, summary) = interrupt_race(&config, &args, Duration::from_secs(4), "INT").await;
+
+    assert_eq!(status.code(), Some(130));
+    let fast_races = stream_metrics(&summary, "slot", "Fast")["total_races"].as_u64().unwrap();
+    assert!(fast_races > 10, "only {} races before the interrupt", fast_races);
+    assert_eq!(ranking(&summary, "slot"), vec!["Fast", "Slow"]);
+
+    // Every race that was open at the signal is closed and exported
+    let exported = std::fs::read_to_string(&export).unwrap();
+    let rows: Vec<&str> = exported.lines().skip(1).collect();
+    assert!(rows.len() as u64 >= fast_races, "{} rows exported", rows.len());
+}
+
+#[tokio::test]
+async fn websocket_streams_race_against_grpc() {
+    let chain = chain(40);
+    let grpc = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let websocket = spawn_ws_mock(chain, StreamBehavior::delayed(25)).await;
+
+    let dir = test_dir("websocket");
+    let streams = [("Grpc", MockStream::Grpc(grpc)), ("PubSub", MockStream::Websocket(websocket))];
+    let config = write_mixed_config(&dir, 20, 3, &streams, "commitment = \"processed\"");
+    let summary = run_race(&config, &[]).await;
+
+    assert_eq!(ranking(&summary, "slot"), vec!["Grpc", "PubSub"]);
+    let pubsub = stream_metrics(&summary, "slot", "PubSub");
+    assert_eq!(pubsub["total_races"], 20);
+    assert!((20.0..40.0).contains(&median(pubsub)), "websocket median {}", median(pubsub));
+}
+
+#[tokio::test]
+async fn first_shred_of_each_slot_races_ahead_of_grpc() {
+    let chain = chain(40);
+    let grpc = spawn_mock(chain, StreamBehavior::delayed(30)).await;
+    let shreds = free_udp_addr();
+    spawn_shred_replayer(chain, StreamBehavior::delayed(0), shreds);
+
+    let dir = test_dir("shreds");
+    let streams = [("Grpc", MockStream::Grpc(grpc)), ("Shreds", MockStream::Shreds(shreds))];
+    let config = write_mixed_config(&dir, 20, 3, &streams, "commitment = \"processed\"");
+    let summary = run_race(&config, &[]).await;
+
+    assert_eq!(ranking(&summary, "slot"), vec!["Shreds", "Grpc"]);
+    // Every slot is reported once, however many shreds and retransmits it arrives as
+    let shreds = stream_metrics(&summary, "slot", "Shreds");
+    assert_eq!(shreds["wins"], 20);
+    assert_eq!(shreds["duplicates"], 0);
+    assert_eq!(shreds["reorders"], 0);
+    let grpc = stream_metrics(&summary, "slot", "Grpc");
+    assert!((25.0..45.0).contains(&median(grpc)), "grpc median {}", median(grpc));
+}
+
+#[tokio::test]
+async fn recorded_race_replays_to_the_same_result() {
+    let chain = chain(40);
+    let fast = spawn_mock(chain, StreamBehavior::delayed(0)).await;
+    let slow = spawn_mock(chain, StreamBehavior {
+        drops: HashSet::from([8, 12]),
+        ..StreamBehavior::delayed(20)
+    }).await;
+
+    let dir = test_dir("replay");
+    let recording = dir.join("recording.ndjson");
+    let config = write_config(&dir, 20, 3, &[("Fast", fast), ("Slow", slow)], "");
+    let args = ["--export", recording.to_str().unwrap(), "--export-format", "ndjson"];
+    let recorded = run_race(&config, &args).await;
+
+    // The recording starts after the warmup, so nothing is skipped again
+    let streams = [("Fast", MockStream::Replay(recording.clone())), ("Slow", MockStream::Replay(recording))];
+    let config = write_mixed_config(&dir, 20, 0, &streams, "");
+    let replayed = run_race(&config, &[]).await;
+
+    assert_eq!(ranking(&replayed, "slot"), ranking(&recorded, "slot"));
+    for name in ["Fast", "Slow"] {
+        let before = stream_metrics(&recorded, "slot", name);
+        let after = stream_metrics(&replayed, "slot", name);
+        assert_eq!(after["wins"], before["wins"], "{} wins", name);
+        assert_eq!(after["missed"], before["missed"], "{} missed", name);
+        let drift = (median(after) - median(before)).abs();
+        assert!(drift < 5.0, "{} median {} replayed as {}", name, median(before), median(after));
+    }
+}